@@ -0,0 +1,370 @@
+use std::collections::BTreeMap;
+
+use serde_json::json;
+use tower_lsp::lsp_types::{
+    CallHierarchyIncomingCall, CallHierarchyIncomingCallsParams, CallHierarchyItem,
+    CallHierarchyOutgoingCall, CallHierarchyOutgoingCallsParams, CallHierarchyPrepareParams, Range,
+    SymbolKind, Url,
+};
+
+use crate::{
+    document::DocumentData,
+    rename::predicate::get_predicate_at_position,
+    utils::range::to_lsp_range,
+    workspace::{
+        dependency_graph::{Definition, DependencyGraph, Predicate},
+        program::Program,
+    },
+};
+
+#[cfg(test)]
+use crate::test_utils::create_test_document;
+#[cfg(test)]
+use std::str::FromStr;
+#[cfg(test)]
+use tower_lsp::lsp_types::Position;
+
+/**
+ * Create a call hierarchy item for the predicate at the position, there is one item for every file that defines it
+ */
+pub fn prepare(
+    document: &DocumentData,
+    program: &Program,
+    params: &CallHierarchyPrepareParams,
+) -> Option<Vec<CallHierarchyItem>> {
+    let (identifier, arity, range) =
+        get_predicate_at_position(document, params.text_document_position_params.position)?;
+    let predicate = (identifier, arity);
+    let graph = DependencyGraph::new(program);
+
+    let mut items: Vec<CallHierarchyItem> = Vec::new();
+    for definition in graph
+        .definitions
+        .iter()
+        .filter(|definition| definition.predicate == predicate)
+    {
+        if !items.iter().any(|item| item.uri == definition.uri) {
+            items.push(create_item(definition));
+        }
+    }
+
+    if items.is_empty() {
+        let range = to_lsp_range(range);
+        items.push(create_lsp_item(&predicate, document.uri.clone(), range, range));
+    }
+
+    Some(items)
+}
+
+/**
+ * Find the rules whose body contains the predicate, the callers are the predicates derived by these rules.
+ * Every rule is a call of its own, so the call sites are always inside of the rule the item spans
+ */
+pub fn incoming(
+    program: &Program,
+    params: &CallHierarchyIncomingCallsParams,
+) -> Option<Vec<CallHierarchyIncomingCall>> {
+    let predicate = get_item_predicate(&params.item)?;
+    let graph = DependencyGraph::new(program);
+
+    // The call sites are grouped by the calling predicate and the rule they occur in
+    let mut calls: BTreeMap<(Predicate, String, usize), (CallHierarchyItem, Vec<Range>)> =
+        BTreeMap::new();
+    for dependency in graph
+        .dependencies
+        .iter()
+        .filter(|dependency| dependency.body == predicate)
+    {
+        // The head of the rule the dependency comes from
+        let definition = match graph.definitions.iter().find(|definition| {
+            definition.predicate == dependency.head
+                && definition.uri == dependency.uri
+                && definition.statement == dependency.statement
+        }) {
+            Some(definition) => definition,
+            None => continue,
+        };
+
+        let (_, ranges) = calls
+            .entry((
+                dependency.head.clone(),
+                dependency.uri.to_string(),
+                dependency.statement.start_byte,
+            ))
+            .or_insert_with(|| (create_item(definition), Vec::new()));
+
+        let range = to_lsp_range(dependency.range);
+        if !ranges.contains(&range) {
+            ranges.push(range);
+        }
+    }
+
+    Some(
+        calls
+            .into_values()
+            .map(|(from, from_ranges)| CallHierarchyIncomingCall { from, from_ranges })
+            .collect(),
+    )
+}
+
+/**
+ * Find the predicates the rules of the item depend on, only rules in the file of the item are considered
+ */
+pub fn outgoing(
+    program: &Program,
+    params: &CallHierarchyOutgoingCallsParams,
+) -> Option<Vec<CallHierarchyOutgoingCall>> {
+    let predicate = get_item_predicate(&params.item)?;
+    let graph = DependencyGraph::new(program);
+
+    let mut calls: BTreeMap<Predicate, Vec<Range>> = BTreeMap::new();
+    for dependency in graph.get_dependencies(&predicate) {
+        if dependency.uri != params.item.uri {
+            continue;
+        }
+
+        let ranges = calls.entry(dependency.body.clone()).or_default();
+        let range = to_lsp_range(dependency.range);
+        if !ranges.contains(&range) {
+            ranges.push(range);
+        }
+    }
+
+    Some(
+        calls
+            .into_iter()
+            .map(|(body, from_ranges)| {
+                // Point to a definition of the called predicate, preferably in the same file
+                let definition = graph
+                    .definitions
+                    .iter()
+                    .filter(|definition| definition.uri == params.item.uri)
+                    .chain(graph.definitions.iter())
+                    .find(|definition| definition.predicate == body);
+
+                let to = match definition {
+                    Some(definition) => create_item(definition),
+                    None => create_lsp_item(
+                        &body,
+                        params.item.uri.clone(),
+                        from_ranges[0],
+                        from_ranges[0],
+                    ),
+                };
+
+                CallHierarchyOutgoingCall { to, from_ranges }
+            })
+            .collect(),
+    )
+}
+
+/**
+ * The item of a definition spans the whole rule, the identifier of the head predicate is selected
+ */
+fn create_item(definition: &Definition) -> CallHierarchyItem {
+    create_lsp_item(
+        &definition.predicate,
+        definition.uri.clone(),
+        to_lsp_range(definition.statement),
+        to_lsp_range(definition.range),
+    )
+}
+
+fn create_lsp_item(
+    predicate: &Predicate,
+    uri: Url,
+    range: Range,
+    selection_range: Range,
+) -> CallHierarchyItem {
+    CallHierarchyItem {
+        name: format!("{}/{}", predicate.0, predicate.1),
+        kind: SymbolKind::FUNCTION,
+        tags: None,
+        detail: None,
+        uri,
+        range,
+        selection_range,
+        data: Some(json!({ "identifier": predicate.0, "arity": predicate.1 })),
+    }
+}
+
+/**
+ * Read the predicate of an item that was created by this server
+ */
+fn get_item_predicate(item: &CallHierarchyItem) -> Option<Predicate> {
+    let data = item.data.as_ref()?;
+
+    Some((
+        data.get("identifier")?.as_str()?.to_string(),
+        data.get("arity")?.as_u64()? as usize,
+    ))
+}
+
+#[cfg(test)]
+fn get_test_program() -> Program {
+    let document = create_test_document(
+        "reach(X) :- start(X).\nreach(Y) :- reach(X), edge(X,Y).\nunreached(X) :- node(X), not reach(X)."
+            .to_string(),
+    );
+    let instance = DocumentData::from_source(
+        Url::from_str("file:///instance.lp").unwrap(),
+        "node(1). edge(1,2). start(1).",
+        1,
+    );
+    Program::new(vec![document, instance])
+}
+
+#[cfg(test)]
+fn get_test_item(program: &Program, line: u32, character: u32) -> CallHierarchyItem {
+    use tower_lsp::lsp_types::{TextDocumentIdentifier, TextDocumentPositionParams};
+
+    let document = &program.documents[0];
+    let params = CallHierarchyPrepareParams {
+        text_document_position_params: TextDocumentPositionParams {
+            text_document: TextDocumentIdentifier::new(document.uri.clone()),
+            position: Position::new(line, character),
+        },
+        work_done_progress_params: Default::default(),
+    };
+
+    let mut items = prepare(document, program, &params).unwrap();
+    assert_eq!(items.len(), 1);
+    items.remove(0)
+}
+
+#[test]
+fn item_should_point_to_the_first_definition() {
+    let program = get_test_program();
+    let item = get_test_item(&program, 2, 30);
+
+    assert_eq!(item.name, "reach/1");
+    assert_eq!(item.uri, program.documents[0].uri);
+    assert_eq!(
+        item.range,
+        Range::new(Position::new(0, 0), Position::new(0, 21))
+    );
+    assert_eq!(
+        item.selection_range,
+        Range::new(Position::new(0, 0), Position::new(0, 5))
+    );
+}
+
+#[test]
+fn incoming_calls_should_be_the_rules_using_the_predicate() {
+    let program = get_test_program();
+    let item = get_test_item(&program, 0, 0);
+
+    let calls = incoming(
+        &program,
+        &CallHierarchyIncomingCallsParams {
+            item,
+            work_done_progress_params: Default::default(),
+            partial_result_params: Default::default(),
+        },
+    )
+    .unwrap();
+
+    let callers: Vec<(String, Vec<Position>)> = calls
+        .iter()
+        .map(|call| {
+            (
+                call.from.name.clone(),
+                call.from_ranges.iter().map(|range| range.start).collect(),
+            )
+        })
+        .collect();
+    assert_eq!(
+        callers,
+        vec![
+            ("reach/1".to_string(), vec![Position::new(1, 12)]),
+            ("unreached/1".to_string(), vec![Position::new(2, 29)]),
+        ]
+    );
+}
+
+#[test]
+fn incoming_calls_should_be_split_by_rule() {
+    let document = create_test_document("p :- q.\np :- r, q, q.".to_string());
+    let program = Program::new(vec![document]);
+    let item = get_test_item(&program, 0, 5);
+
+    let calls = incoming(
+        &program,
+        &CallHierarchyIncomingCallsParams {
+            item,
+            work_done_progress_params: Default::default(),
+            partial_result_params: Default::default(),
+        },
+    )
+    .unwrap();
+
+    let callers: Vec<(Range, Vec<Position>)> = calls
+        .iter()
+        .map(|call| {
+            (
+                call.from.range,
+                call.from_ranges.iter().map(|range| range.start).collect(),
+            )
+        })
+        .collect();
+    assert_eq!(
+        callers,
+        vec![
+            (
+                Range::new(Position::new(0, 0), Position::new(0, 7)),
+                vec![Position::new(0, 5)]
+            ),
+            (
+                Range::new(Position::new(1, 0), Position::new(1, 13)),
+                vec![Position::new(1, 8), Position::new(1, 11)]
+            ),
+        ]
+    );
+}
+
+#[test]
+fn outgoing_calls_should_point_to_the_definitions_of_the_body_predicates() {
+    let program = get_test_program();
+    let item = get_test_item(&program, 0, 0);
+
+    let calls = outgoing(
+        &program,
+        &CallHierarchyOutgoingCallsParams {
+            item,
+            work_done_progress_params: Default::default(),
+            partial_result_params: Default::default(),
+        },
+    )
+    .unwrap();
+
+    let callees: Vec<(String, String, Vec<Position>)> = calls
+        .iter()
+        .map(|call| {
+            (
+                call.to.name.clone(),
+                call.to.uri.to_string(),
+                call.from_ranges.iter().map(|range| range.start).collect(),
+            )
+        })
+        .collect();
+    assert_eq!(
+        callees,
+        vec![
+            (
+                "edge/2".to_string(),
+                "file:///instance.lp".to_string(),
+                vec![Position::new(1, 22)]
+            ),
+            (
+                "reach/1".to_string(),
+                program.documents[0].uri.to_string(),
+                vec![Position::new(1, 12)]
+            ),
+            (
+                "start/1".to_string(),
+                "file:///instance.lp".to_string(),
+                vec![Position::new(0, 12)]
+            ),
+        ]
+    );
+}
@@ -0,0 +1,103 @@
+use serde_json::{json, Value};
+use tower_lsp::lsp_types::{DiagnosticSeverity, Position};
+
+use super::{get_code, get_rule_id, get_severity_name, FileDiagnostics};
+
+#[cfg(test)]
+use std::path::PathBuf;
+#[cfg(test)]
+use tower_lsp::lsp_types::{Diagnostic, NumberOrString, Range};
+
+/**
+ * The version of the json output, it is increased whenever a field changes its meaning or is removed
+ */
+const SCHEMA_VERSION: u32 = 1;
+
+/**
+ * Format the diagnostics as json, lines and columns start at 1 like in the text output
+ */
+pub fn format_json(results: &[FileDiagnostics]) -> String {
+    let files: Vec<Value> = results
+        .iter()
+        .map(|result| {
+            let diagnostics: Vec<Value> = result
+                .diagnostics
+                .iter()
+                .map(|diagnostic| {
+                    json!({
+                        "code": get_code(diagnostic),
+                        "rule": get_rule_id(diagnostic),
+                        "source": diagnostic.source.clone().unwrap_or_default(),
+                        "severity": get_severity_name(diagnostic.severity),
+                        "message": diagnostic.message,
+                        "range": {
+                            "start": format_position(diagnostic.range.start),
+                            "end": format_position(diagnostic.range.end),
+                        },
+                    })
+                })
+                .collect();
+
+            json!({
+                "path": result.path.display().to_string(),
+                "diagnostics": diagnostics,
+            })
+        })
+        .collect();
+
+    let count = |severity: DiagnosticSeverity| {
+        results
+            .iter()
+            .flat_map(|result| result.diagnostics.iter())
+            .filter(|diagnostic| diagnostic.severity == Some(severity))
+            .count()
+    };
+
+    let output = json!({
+        "version": SCHEMA_VERSION,
+        "files": files,
+        "summary": {
+            "files": results.len(),
+            "errors": count(DiagnosticSeverity::ERROR),
+            "warnings": count(DiagnosticSeverity::WARNING),
+        },
+    });
+
+    serde_json::to_string_pretty(&output).unwrap()
+}
+
+fn format_position(position: Position) -> Value {
+    json!({
+        "line": position.line + 1,
+        "column": position.character + 1,
+    })
+}
+
+#[test]
+fn json_should_contain_every_diagnostic() {
+    let results = vec![FileDiagnostics {
+        path: PathBuf::from("encoding.lp"),
+        diagnostics: vec![Diagnostic::new(
+            Range::new(Position::new(1, 2), Position::new(1, 3)),
+            Some(DiagnosticSeverity::ERROR),
+            Some(NumberOrString::Number(2000)),
+            Some("clinlint".to_string()),
+            "'X' is unsafe".to_string(),
+            None,
+            None,
+        )],
+    }];
+
+    let output: Value = serde_json::from_str(&format_json(&results)).unwrap();
+
+    assert_eq!(output["version"], 1);
+    assert_eq!(output["summary"]["errors"], 1);
+    assert_eq!(output["files"][0]["path"], "encoding.lp");
+
+    let diagnostic = &output["files"][0]["diagnostics"][0];
+    assert_eq!(diagnostic["code"], "2000");
+    assert_eq!(diagnostic["rule"], "UnsafeVariable");
+    assert_eq!(diagnostic["source"], "clinlint");
+    assert_eq!(diagnostic["range"]["start"]["line"], 2);
+    assert_eq!(diagnostic["range"]["start"]["column"], 3);
+}
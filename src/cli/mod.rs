@@ -0,0 +1,492 @@
+use std::{
+    io::{ErrorKind, Write},
+    path::{Path, PathBuf},
+};
+
+use dashmap::DashMap;
+use tower_lsp::lsp_types::{Diagnostic, DiagnosticSeverity, NumberOrString, Url};
+
+use crate::{
+    diagnostics::{
+        diagnostic_codes::DiagnosticsCode,
+        diagnostic_settings::{DiagnosticLevel, DiagnosticSettings},
+        run_diagnostics,
+    },
+    document::DocumentData,
+    workspace::{find_encodings, Workspace},
+};
+
+use self::{json::format_json, sarif::format_sarif};
+
+#[cfg(test)]
+use crate::test_utils::TestFolder;
+
+pub mod json;
+pub mod sarif;
+
+const USAGE: &str = "Usage: asp-lsp check [--format text|json|sarif] [--enable <rule>] [--level <rule>=<level>] <file or folder>...
+
+Runs the diagnostics of the language server on the given encodings and prints every problem.
+Folders are searched for .lp files. Without a command the language server is started on stdio.
+
+Rules: arity-mismatch (off by default), unused-predicate (hint), unmatched-show (warning),
+       singleton-variable (warning), non-stratified-negation (information),
+       odd-loop (information)
+Levels: off, error, warning, information, hint. --enable <rule> reports a rule as a warning.
+The level of any other code can be changed with its rule id, e.g. --level UnsafeVariable=warning";
+
+/**
+ * The formats the check command can print the diagnostics in
+ */
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub enum OutputFormat {
+    Text,
+    Json,
+    Sarif,
+}
+
+/**
+ * The parsed arguments of the check command
+ */
+#[derive(Debug, Eq, PartialEq)]
+struct CheckArguments {
+    format: OutputFormat,
+    settings: DiagnosticSettings,
+    files: Vec<String>,
+}
+
+/**
+ * The maximum number of problems reported per file, this is the same limit the language server uses
+ */
+const MAXIMUM_NUMBER_OF_PROBLEMS: u32 = 100;
+
+/**
+ * The diagnostics that were found in a file
+ */
+#[derive(Debug)]
+pub struct FileDiagnostics {
+    pub path: PathBuf,
+    pub diagnostics: Vec<Diagnostic>,
+}
+
+/**
+ * Run the check command and return the exit code of the process.
+ * The exit code is 1 if any error was found and 2 if the files could not be checked
+ */
+pub fn run_check(arguments: &[String]) -> i32 {
+    if arguments.iter().any(|argument| argument == "--help") {
+        eprintln!("{}", USAGE);
+        return 2;
+    }
+
+    let arguments = match parse_arguments(arguments) {
+        Ok(parsed) => parsed,
+        Err(message) => {
+            eprintln!("{}\n\n{}", message, USAGE);
+            return 2;
+        }
+    };
+
+    let paths = match collect_paths(&arguments.files) {
+        Ok(paths) => paths,
+        Err(message) => {
+            eprintln!("{}", message);
+            return 2;
+        }
+    };
+
+    let results = match check_files(&paths, &arguments.settings) {
+        Ok(results) => results,
+        Err(message) => {
+            eprintln!("{}", message);
+            return 2;
+        }
+    };
+
+    if let Err(error) = write_results(&mut std::io::stdout().lock(), arguments.format, &results) {
+        // A reader like head may stop reading early, the files were checked nevertheless
+        if error.kind() != ErrorKind::BrokenPipe {
+            eprintln!("Could not write the results: {}", error);
+            return 2;
+        }
+    }
+    eprintln!("{}", format_summary(&results));
+
+    get_exit_code(&results)
+}
+
+/**
+ * Write the diagnostics in the format, write errors are returned instead of panicking like print! does
+ */
+fn write_results(
+    output: &mut impl Write,
+    format: OutputFormat,
+    results: &[FileDiagnostics],
+) -> std::io::Result<()> {
+    match format {
+        OutputFormat::Text => write!(output, "{}", format_text(results))?,
+        OutputFormat::Json => writeln!(output, "{}", format_json(results))?,
+        OutputFormat::Sarif => writeln!(output, "{}", format_sarif(results))?,
+    }
+
+    output.flush()
+}
+
+/**
+ * Split the arguments into the output format, the enabled diagnostics and the files that should be checked
+ */
+fn parse_arguments(arguments: &[String]) -> Result<CheckArguments, String> {
+    let mut format = OutputFormat::Text;
+    let mut settings = DiagnosticSettings::default();
+    let mut files = Vec::new();
+    let mut arguments = arguments.iter();
+
+    while let Some(argument) = arguments.next() {
+        let enabled = if argument == "--enable" {
+            Some(arguments.next().map(String::as_str))
+        } else {
+            argument.strip_prefix("--enable=").map(Some)
+        };
+
+        if let Some(enabled) = enabled {
+            let rule = enabled.ok_or("Missing rule to enable")?;
+            *get_rule_level(&mut settings, rule)? = DiagnosticLevel::Warning;
+            continue;
+        }
+
+        let level = if argument == "--level" {
+            Some(arguments.next().map(String::as_str))
+        } else {
+            argument.strip_prefix("--level=").map(Some)
+        };
+
+        if let Some(level) = level {
+            let (rule, level) = level
+                .and_then(|level| level.split_once('='))
+                .ok_or("Expected --level <rule>=<level>")?;
+            *get_rule_level(&mut settings, rule)? = DiagnosticLevel::from_name(level)
+                .ok_or_else(|| format!("Unknown level '{}'", level))?;
+            continue;
+        }
+
+        let value = if argument == "--format" {
+            arguments.next().map(String::as_str)
+        } else if let Some(value) = argument.strip_prefix("--format=") {
+            Some(value)
+        } else {
+            files.push(argument.clone());
+            continue;
+        };
+
+        format = match value {
+            Some("text") => OutputFormat::Text,
+            Some("json") => OutputFormat::Json,
+            Some("sarif") => OutputFormat::Sarif,
+            Some(other) => return Err(format!("Unknown output format '{}'", other)),
+            None => return Err("Missing output format".to_string()),
+        };
+    }
+
+    if files.is_empty() {
+        return Err("No files to check".to_string());
+    }
+
+    Ok(CheckArguments {
+        format,
+        settings,
+        files,
+    })
+}
+
+/**
+ * Get the level of a rule by the name it has on the command line
+ */
+fn get_rule_level<'a>(
+    settings: &'a mut DiagnosticSettings,
+    rule: &str,
+) -> Result<&'a mut DiagnosticLevel, String> {
+    match rule {
+        "arity-mismatch" => Ok(&mut settings.arity_mismatch),
+        "unused-predicate" => Ok(&mut settings.unused_predicate),
+        "unmatched-show" => Ok(&mut settings.unmatched_show),
+        "singleton-variable" => Ok(&mut settings.singleton_variable),
+        "non-stratified-negation" => Ok(&mut settings.non_stratified_negation),
+        "odd-loop" => Ok(&mut settings.odd_loop),
+        // Every other code can be overridden by its rule id
+        _ if DiagnosticsCode::ALL
+            .into_iter()
+            .any(|code| code.get_rule_id() == rule) =>
+        {
+            Ok(settings
+                .codes
+                .entry(rule.to_string())
+                .or_insert(DiagnosticLevel::Warning))
+        }
+        _ => Err(format!("Unknown rule '{}'", rule)),
+    }
+}
+
+/**
+ * Turn the arguments into a list of encodings, folders are replaced by the encodings inside of them
+ */
+fn collect_paths(arguments: &[String]) -> Result<Vec<PathBuf>, String> {
+    let mut paths = Vec::new();
+
+    for argument in arguments {
+        let path = PathBuf::from(argument);
+
+        if path.is_dir() {
+            let mut encodings = find_encodings(&path);
+            encodings.sort();
+            paths.extend(encodings);
+        } else if path.is_file() {
+            paths.push(path);
+        } else {
+            return Err(format!("{}: no such file or folder", argument));
+        }
+    }
+
+    Ok(paths)
+}
+
+/**
+ * Load every file as a document and run the diagnostics on it, included files are read from disk
+ */
+pub fn check_files(
+    paths: &[PathBuf],
+    settings: &DiagnosticSettings,
+) -> Result<Vec<FileDiagnostics>, String> {
+    let workspace = Workspace::new();
+    let mut documents = Vec::new();
+
+    // Every file is read first, so that files included by others are checked within the files including them
+    for path in paths {
+        let source = std::fs::read_to_string(path)
+            .map_err(|error| format!("{}: {}", path.display(), error))?;
+
+        let uri = get_uri(path);
+        let document = DocumentData::from_source(uri.clone(), &source, 0);
+        workspace.files.insert(uri.to_string(), document.clone());
+        documents.push(document);
+    }
+
+    let mut results = Vec::new();
+    for (path, document) in paths.iter().zip(documents) {
+        let program = workspace.get_root_program(&document.uri, &DashMap::new());
+        let mut diagnostics =
+            run_diagnostics(document, &program, settings, MAXIMUM_NUMBER_OF_PROBLEMS);
+        diagnostics.sort_by_key(|diagnostic| {
+            (
+                diagnostic.range.start.line,
+                diagnostic.range.start.character,
+            )
+        });
+
+        results.push(FileDiagnostics {
+            path: path.clone(),
+            diagnostics,
+        });
+    }
+
+    Ok(results)
+}
+
+/**
+ * Documents are identified by their uri, relative paths are made absolute for this
+ */
+fn get_uri(path: &Path) -> Url {
+    let absolute = std::fs::canonicalize(path).unwrap_or_else(|_| path.to_path_buf());
+
+    Url::from_file_path(&absolute).unwrap_or_else(|_| Url::parse("file:///unknown.lp").unwrap())
+}
+
+/**
+ * Format the diagnostics as file:line:col messages, lines and columns start at 1
+ */
+pub fn format_text(results: &[FileDiagnostics]) -> String {
+    let mut output = String::new();
+
+    for result in results {
+        for diagnostic in &result.diagnostics {
+            output.push_str(&format!(
+                "{}:{}:{}: {}[{}]: {}\n",
+                result.path.display(),
+                diagnostic.range.start.line + 1,
+                diagnostic.range.start.character + 1,
+                get_severity_name(diagnostic.severity),
+                get_code(diagnostic),
+                diagnostic.message
+            ));
+        }
+    }
+
+    output
+}
+
+/**
+ * Summarize how many problems were found in how many files
+ */
+fn format_summary(results: &[FileDiagnostics]) -> String {
+    let errors = count_errors(results);
+    let others: usize = results
+        .iter()
+        .map(|result| result.diagnostics.len())
+        .sum::<usize>()
+        - errors;
+
+    format!(
+        "{} error(s), {} other problem(s) in {} file(s)",
+        errors,
+        others,
+        results.len()
+    )
+}
+
+fn count_errors(results: &[FileDiagnostics]) -> usize {
+    results
+        .iter()
+        .flat_map(|result| result.diagnostics.iter())
+        .filter(|diagnostic| diagnostic.severity == Some(DiagnosticSeverity::ERROR))
+        .count()
+}
+
+/**
+ * The process fails if any error was found, warnings and hints do not fail it
+ */
+pub fn get_exit_code(results: &[FileDiagnostics]) -> i32 {
+    if count_errors(results) > 0 {
+        1
+    } else {
+        0
+    }
+}
+
+pub fn get_severity_name(severity: Option<DiagnosticSeverity>) -> &'static str {
+    match severity {
+        Some(DiagnosticSeverity::ERROR) => "error",
+        Some(DiagnosticSeverity::WARNING) => "warning",
+        Some(DiagnosticSeverity::INFORMATION) => "info",
+        Some(DiagnosticSeverity::HINT) => "hint",
+        _ => "error",
+    }
+}
+
+/**
+ * Get the code of a diagnostic as text, the diagnostics of this server always have a number as code
+ */
+pub fn get_code(diagnostic: &Diagnostic) -> String {
+    match &diagnostic.code {
+        Some(NumberOrString::Number(number)) => number.to_string(),
+        Some(NumberOrString::String(code)) => code.clone(),
+        None => String::new(),
+    }
+}
+
+/**
+ * Get the rule a diagnostic belongs to, this is the name of its diagnostics code
+ */
+pub fn get_rule_id(diagnostic: &Diagnostic) -> String {
+    match &diagnostic.code {
+        Some(NumberOrString::Number(number)) => match DiagnosticsCode::from_i32(*number) {
+            Some(code) => code.get_rule_id().to_string(),
+            None => number.to_string(),
+        },
+        _ => get_code(diagnostic),
+    }
+}
+
+#[test]
+fn arguments_should_select_the_output_format() {
+    let arguments = |arguments: &[&str]| -> Vec<String> {
+        arguments
+            .iter()
+            .map(|argument| argument.to_string())
+            .collect()
+    };
+
+    assert_eq!(
+        parse_arguments(&arguments(&["a.lp", "--format", "sarif", "b.lp"])).unwrap(),
+        CheckArguments {
+            format: OutputFormat::Sarif,
+            settings: DiagnosticSettings::default(),
+            files: arguments(&["a.lp", "b.lp"])
+        }
+    );
+    assert_eq!(
+        parse_arguments(&arguments(&["--format=json", "a.lp"]))
+            .unwrap()
+            .format,
+        OutputFormat::Json
+    );
+    assert_eq!(
+        parse_arguments(&arguments(&["a.lp"])).unwrap().format,
+        OutputFormat::Text
+    );
+
+    let settings = parse_arguments(&arguments(&[
+        "--enable",
+        "arity-mismatch",
+        "--level=unused-predicate=error",
+        "--level=UnsafeVariable=warning",
+        "a.lp",
+    ]))
+    .unwrap()
+    .settings;
+    assert_eq!(settings.arity_mismatch, DiagnosticLevel::Warning);
+    assert_eq!(settings.unused_predicate, DiagnosticLevel::Error);
+    assert_eq!(
+        settings.get_override(DiagnosticsCode::UnsafeVariable),
+        Some(DiagnosticLevel::Warning)
+    );
+    assert!(parse_arguments(&arguments(&["--enable=unknown", "a.lp"])).is_err());
+    assert!(parse_arguments(&arguments(&["--level", "unmatched-show=loud", "a.lp"])).is_err());
+    assert!(parse_arguments(&arguments(&["--format", "xml", "a.lp"])).is_err());
+    assert!(parse_arguments(&arguments(&["--format", "json"])).is_err());
+}
+
+#[test]
+fn check_should_report_problems_with_positions() {
+    let directory = std::env::temp_dir().join("asp_lsp_cli_check");
+    std::fs::create_dir_all(&directory).unwrap();
+    std::fs::write(directory.join("safe.lp"), "a(1).\nb(X) :- a(X).").unwrap();
+    std::fs::write(directory.join("unsafe.lp"), "a(1).\nb(X) :- a(Y).").unwrap();
+
+    let results = check_files(
+        &collect_paths(&[directory.display().to_string()]).unwrap(),
+        &DiagnosticSettings::default(),
+    )
+    .unwrap();
+
+    assert_eq!(results.len(), 2);
+    assert_eq!(
+        format_text(&results),
+        format!(
+            "{0}:2:3: error[2000]: 'X' is unsafe\n{0}:2:11: warning[2005]: 'Y' occurs only once, use '_' instead\n",
+            directory.join("unsafe.lp").display()
+        )
+    );
+    assert_eq!(get_exit_code(&results), 1);
+    assert_eq!(get_exit_code(&results[..1]), 0);
+}
+
+#[test]
+fn included_files_should_see_the_definitions_of_their_siblings() {
+    let folder = TestFolder::new("cli_siblings");
+    let directory = &folder.path;
+    std::fs::write(
+        directory.join("main.lp"),
+        "#include \"defs.lp\". #include \"rules.lp\".",
+    )
+    .unwrap();
+    std::fs::write(directory.join("defs.lp"), "edge(1,2).").unwrap();
+    std::fs::write(directory.join("rules.lp"), "reach(X) :- edge(X,_).").unwrap();
+
+    let results = check_files(
+        &collect_paths(&[directory.display().to_string()]).unwrap(),
+        &DiagnosticSettings::default(),
+    )
+    .unwrap();
+
+    assert_eq!(results.len(), 3);
+    assert_eq!(format_text(&results), "");
+}
@@ -0,0 +1,178 @@
+use serde_json::{json, Value};
+use tower_lsp::lsp_types::{Diagnostic, DiagnosticSeverity};
+
+use crate::diagnostics::diagnostic_codes::DiagnosticsCode;
+
+use super::{get_rule_id, FileDiagnostics};
+
+#[cfg(test)]
+use std::path::PathBuf;
+#[cfg(test)]
+use tower_lsp::lsp_types::{NumberOrString, Position, Range};
+
+const SARIF_SCHEMA: &str = "https://json.schemastore.org/sarif-2.1.0.json";
+
+/**
+ * The tools that create diagnostics, every tool is a separate run in the sarif log
+ */
+const SOURCES: [&str; 2] = ["tree-sitter", "clinlint"];
+
+/**
+ * Format the diagnostics as a SARIF 2.1.0 log
+ */
+pub fn format_sarif(results: &[FileDiagnostics]) -> String {
+    let runs: Vec<Value> = SOURCES
+        .iter()
+        .map(|source| create_run(source, results))
+        .collect();
+
+    let output = json!({
+        "$schema": SARIF_SCHEMA,
+        "version": "2.1.0",
+        "runs": runs,
+    });
+
+    serde_json::to_string_pretty(&output).unwrap()
+}
+
+/**
+ * Create the run of a single tool, containing its rules and the results it found
+ */
+fn create_run(source: &str, results: &[FileDiagnostics]) -> Value {
+    let diagnostics: Vec<(&FileDiagnostics, &Diagnostic)> = results
+        .iter()
+        .flat_map(|result| {
+            result
+                .diagnostics
+                .iter()
+                .map(move |diagnostic| (result, diagnostic))
+        })
+        .filter(|(_, diagnostic)| diagnostic.source.as_deref() == Some(source))
+        .collect();
+
+    // Every rule that was reported is listed once, results refer to it by its index
+    let mut rule_ids: Vec<String> = Vec::new();
+    for (_, diagnostic) in &diagnostics {
+        let rule_id = get_rule_id(diagnostic);
+        if !rule_ids.contains(&rule_id) {
+            rule_ids.push(rule_id);
+        }
+    }
+
+    let rules: Vec<Value> = rule_ids
+        .iter()
+        .map(|rule_id| create_rule(rule_id))
+        .collect();
+
+    let sarif_results: Vec<Value> = diagnostics
+        .iter()
+        .map(|(result, diagnostic)| {
+            let rule_id = get_rule_id(diagnostic);
+
+            json!({
+                "ruleId": rule_id,
+                "ruleIndex": rule_ids.iter().position(|id| *id == rule_id),
+                "level": get_level(diagnostic.severity),
+                "message": { "text": diagnostic.message },
+                "locations": [{
+                    "physicalLocation": {
+                        "artifactLocation": {
+                            "uri": result.path.display().to_string().replace('\\', "/"),
+                        },
+                        "region": {
+                            "startLine": diagnostic.range.start.line + 1,
+                            "startColumn": diagnostic.range.start.character + 1,
+                            "endLine": diagnostic.range.end.line + 1,
+                            "endColumn": diagnostic.range.end.character + 1,
+                        },
+                    },
+                }],
+            })
+        })
+        .collect();
+
+    json!({
+        "tool": {
+            "driver": {
+                "name": source,
+                "version": env!("CARGO_PKG_VERSION"),
+                "rules": rules,
+            },
+        },
+        "results": sarif_results,
+    })
+}
+
+/**
+ * Describe a rule, rules of this server are described by their diagnostics code
+ */
+fn create_rule(rule_id: &str) -> Value {
+    match DiagnosticsCode::ALL
+        .into_iter()
+        .find(|code| code.get_rule_id() == rule_id)
+    {
+        Some(code) => json!({
+            "id": rule_id,
+            "name": rule_id,
+            "shortDescription": { "text": code.get_description() },
+            "properties": { "code": code.into_i32() },
+        }),
+        None => json!({ "id": rule_id }),
+    }
+}
+
+fn get_level(severity: Option<DiagnosticSeverity>) -> &'static str {
+    match severity {
+        Some(DiagnosticSeverity::WARNING) => "warning",
+        Some(DiagnosticSeverity::INFORMATION) | Some(DiagnosticSeverity::HINT) => "note",
+        _ => "error",
+    }
+}
+
+#[test]
+fn sarif_should_group_results_by_source() {
+    let create_diagnostic = |code: i32, source: &str| {
+        Diagnostic::new(
+            Range::new(Position::new(0, 0), Position::new(0, 1)),
+            Some(DiagnosticSeverity::ERROR),
+            Some(NumberOrString::Number(code)),
+            Some(source.to_string()),
+            "message".to_string(),
+            None,
+            None,
+        )
+    };
+
+    let results = vec![FileDiagnostics {
+        path: PathBuf::from("encoding.lp"),
+        diagnostics: vec![
+            create_diagnostic(2000, "clinlint"),
+            create_diagnostic(1001, "tree-sitter"),
+            create_diagnostic(2000, "clinlint"),
+        ],
+    }];
+
+    let output: Value = serde_json::from_str(&format_sarif(&results)).unwrap();
+
+    assert_eq!(output["version"], "2.1.0");
+    assert_eq!(output["runs"][0]["tool"]["driver"]["name"], "tree-sitter");
+    assert_eq!(output["runs"][0]["results"][0]["ruleId"], "ExpectedDot");
+
+    let clinlint = &output["runs"][1];
+    assert_eq!(
+        clinlint["tool"]["driver"]["rules"]
+            .as_array()
+            .unwrap()
+            .len(),
+        1
+    );
+    assert_eq!(
+        clinlint["tool"]["driver"]["rules"][0]["id"],
+        "UnsafeVariable"
+    );
+    assert_eq!(clinlint["results"].as_array().unwrap().len(), 2);
+    assert_eq!(
+        clinlint["results"][1]["locations"][0]["physicalLocation"]["region"]["startLine"],
+        1
+    );
+}
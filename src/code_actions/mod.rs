@@ -0,0 +1,310 @@
+use std::collections::HashMap;
+
+use tower_lsp::lsp_types::{
+    CodeAction, CodeActionKind, CodeActionOrCommand, CodeActionParams, CodeActionResponse,
+    Diagnostic, NumberOrString, Position, Range, TextEdit, WorkspaceEdit,
+};
+
+use crate::{
+    diagnostics::diagnostic_codes::DiagnosticsCode, document::DocumentData, utils,
+    workspace::program::Program,
+};
+
+pub mod unsafe_variable;
+
+/**
+ * Create the quick fixes for the diagnostics the client sends with the request
+ */
+pub fn handle(
+    document: &DocumentData,
+    program: &Program,
+    params: &CodeActionParams,
+) -> Option<CodeActionResponse> {
+    let mut actions: Vec<CodeAction> = Vec::new();
+
+    for diagnostic in &params.context.diagnostics {
+        let code = match diagnostic.code {
+            Some(NumberOrString::Number(code)) => DiagnosticsCode::from_i32(code),
+            _ => None,
+        };
+
+        let fixes = match code {
+            Some(DiagnosticsCode::SingletonVariable) => {
+                anonymize_singleton_variable(document, diagnostic)
+                    .into_iter()
+                    .collect()
+            }
+            Some(DiagnosticsCode::ExpectedDot | DiagnosticsCode::ExpectedMissingToken) => {
+                insert_expected_token(document, diagnostic)
+                    .into_iter()
+                    .collect()
+            }
+            Some(DiagnosticsCode::UnsafeVariable) => {
+                unsafe_variable::get_fixes(document, program, diagnostic)
+            }
+            _ => Vec::new(),
+        };
+
+        for fix in fixes {
+            // Every occurence of an unsafe variable has a diagnostic, the same fix is offered once for all of them
+            match actions.iter_mut().find(|action| action.edit == fix.edit) {
+                Some(action) => action
+                    .diagnostics
+                    .get_or_insert_with(Vec::new)
+                    .extend(fix.diagnostics.unwrap_or_default()),
+                None => actions.push(fix),
+            }
+        }
+    }
+
+    Some(
+        actions
+            .into_iter()
+            .map(CodeActionOrCommand::CodeAction)
+            .collect(),
+    )
+}
+
+/**
+ * Replace a variable that occurs only once with the anonymous variable _
+ */
+fn anonymize_singleton_variable(
+    document: &DocumentData,
+    diagnostic: &Diagnostic,
+) -> Option<CodeAction> {
+    // The diagnostic may be outdated, only replace the range if it still contains the variable
+    let node = utils::node::from_position(document, diagnostic.range.start)?;
+    if node.kind() != "VARIABLE" || utils::range::to_lsp_range(node.range()) != diagnostic.range {
+        return None;
+    }
+
+    let name = document.get_source_for_range(node.range());
+    if name.starts_with('_') {
+        return None;
+    }
+
+    Some(create_quick_fix(
+        document,
+        format!("Replace '{}' with '_'", name),
+        TextEdit::new(diagnostic.range, "_".to_string()),
+        diagnostic,
+        true,
+    ))
+}
+
+/**
+ * Insert the token a syntax error expects at the end of the diagnostic, the token is taken from the data of the diagnostic
+ */
+fn insert_expected_token(document: &DocumentData, diagnostic: &Diagnostic) -> Option<CodeAction> {
+    let token = diagnostic.data.as_ref()?.get("insert")?.as_str()?;
+    let position = diagnostic.range.end;
+
+    // The diagnostic may be outdated, do not insert the token a second time
+    if get_character(document, position).is_some_and(|character| token.starts_with(character)) {
+        return None;
+    }
+
+    Some(create_quick_fix(
+        document,
+        format!("Insert '{}'", token),
+        TextEdit::new(Range::new(position, position), token.to_string()),
+        diagnostic,
+        true,
+    ))
+}
+
+/**
+ * Create a quick fix for a diagnostic that applies a single edit to the document
+ */
+fn create_quick_fix(
+    document: &DocumentData,
+    title: String,
+    edit: TextEdit,
+    diagnostic: &Diagnostic,
+    is_preferred: bool,
+) -> CodeAction {
+    CodeAction {
+        title,
+        kind: Some(CodeActionKind::QUICKFIX),
+        diagnostics: Some(vec![diagnostic.clone()]),
+        edit: Some(WorkspaceEdit {
+            changes: Some(HashMap::from([(document.uri.clone(), vec![edit])])),
+            ..Default::default()
+        }),
+        is_preferred: Some(is_preferred),
+        ..Default::default()
+    }
+}
+
+/**
+ * Get the character of the document at a position, if the position is inside the document.
+ * Like the positions of the diagnostics, the character of the position is a byte column
+ */
+fn get_character(document: &DocumentData, position: Position) -> Option<char> {
+    let line = position.line as usize;
+    if line >= document.source.len_lines() {
+        return None;
+    }
+
+    let byte = document.source.line_to_byte(line) + position.character as usize;
+    if byte >= document.source.len_bytes() {
+        return None;
+    }
+
+    // A column in the middle of a multi-byte character points to no character
+    let index = document.source.byte_to_char(byte);
+    if document.source.char_to_byte(index) != byte {
+        return None;
+    }
+
+    Some(document.source.char(index))
+}
+
+#[cfg(test)]
+fn create_test_params(document: &DocumentData, diagnostics: Vec<Diagnostic>) -> CodeActionParams {
+    use tower_lsp::lsp_types::{CodeActionContext, TextDocumentIdentifier};
+
+    CodeActionParams {
+        text_document: TextDocumentIdentifier::new(document.uri.clone()),
+        range: Range::default(),
+        context: CodeActionContext {
+            diagnostics,
+            only: None,
+            trigger_kind: None,
+        },
+        work_done_progress_params: Default::default(),
+        partial_result_params: Default::default(),
+    }
+}
+
+#[test]
+fn singleton_variable_should_be_replaced() {
+    use crate::diagnostics::{diagnostic_settings::DiagnosticSettings, run_diagnostics};
+    use crate::test_utils::create_test_document;
+
+    let document = create_test_document("a(X) :- b(X, Y).".to_string());
+    let program = Program::new(vec![document.clone()]);
+    let diagnostics = run_diagnostics(
+        document.clone(),
+        &program,
+        &DiagnosticSettings::default(),
+        100,
+    );
+
+    let actions = handle(
+        &document,
+        &program,
+        &create_test_params(&document, diagnostics),
+    )
+    .unwrap();
+    assert_eq!(actions.len(), 1);
+
+    let CodeActionOrCommand::CodeAction(action) = &actions[0] else {
+        panic!("Expected a code action");
+    };
+    assert_eq!(action.title, "Replace 'Y' with '_'");
+
+    let edits = &action.edit.as_ref().unwrap().changes.as_ref().unwrap()[&document.uri];
+    assert_eq!(edits.len(), 1);
+    assert_eq!(edits[0].new_text, "_");
+    assert_eq!(edits[0].range.start.character, 13);
+    assert_eq!(edits[0].range.end.character, 14);
+}
+
+#[test]
+fn expected_tokens_should_be_inserted() {
+    use crate::diagnostics::{diagnostic_settings::DiagnosticSettings, run_diagnostics};
+    use crate::test_utils::create_test_document;
+
+    let document = create_test_document("a(1.\n{b.\nc. d e :- c.".to_string());
+    let program = Program::new(vec![document.clone()]);
+    let diagnostics = run_diagnostics(
+        document.clone(),
+        &program,
+        &DiagnosticSettings::default(),
+        100,
+    );
+
+    let actions = handle(
+        &document,
+        &program,
+        &create_test_params(&document, diagnostics),
+    )
+    .unwrap();
+    let edits: Vec<(String, Position, String)> = actions
+        .iter()
+        .filter_map(|action| match action {
+            CodeActionOrCommand::CodeAction(action) => Some(action),
+            _ => None,
+        })
+        .map(|action| {
+            let edit = &action.edit.as_ref().unwrap().changes.as_ref().unwrap()[&document.uri][0];
+            (
+                action.title.clone(),
+                edit.range.start,
+                edit.new_text.clone(),
+            )
+        })
+        .collect();
+
+    assert_eq!(
+        edits,
+        vec![
+            (
+                "Insert '.'".to_string(),
+                Position::new(2, 4),
+                ".".to_string()
+            ),
+            (
+                "Insert ')'".to_string(),
+                Position::new(0, 3),
+                ")".to_string()
+            ),
+            (
+                "Insert '}'".to_string(),
+                Position::new(1, 2),
+                "}".to_string()
+            ),
+        ]
+    );
+}
+
+#[test]
+fn expected_token_should_not_be_inserted_twice() {
+    use crate::test_utils::create_test_document;
+
+    let mut diagnostic = Diagnostic::new_simple(
+        Range::new(Position::new(0, 3), Position::new(0, 3)),
+        "syntax error while parsing, expected: ')'".to_string(),
+    );
+    diagnostic.code = Some(NumberOrString::Number(
+        DiagnosticsCode::ExpectedMissingToken.into_i32(),
+    ));
+    diagnostic.data = Some(serde_json::json!({ "insert": ")" }));
+
+    let document = create_test_document("a(1).".to_string());
+    let program = Program::new(vec![document.clone()]);
+    let actions = handle(
+        &document,
+        &program,
+        &create_test_params(&document, vec![diagnostic.clone()]),
+    )
+    .unwrap();
+
+    assert!(actions.is_empty());
+
+    // The column is a byte column, the dot follows a two byte character
+    diagnostic.range = Range::new(Position::new(0, 7), Position::new(0, 7));
+    diagnostic.data = Some(serde_json::json!({ "insert": "." }));
+
+    let document = create_test_document("a(\"ä\").".to_string());
+    let program = Program::new(vec![document.clone()]);
+    let actions = handle(
+        &document,
+        &program,
+        &create_test_params(&document, vec![diagnostic]),
+    )
+    .unwrap();
+
+    assert!(actions.is_empty());
+}
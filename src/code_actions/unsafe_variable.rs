@@ -0,0 +1,443 @@
+use std::collections::BTreeSet;
+
+use tower_lsp::lsp_types::{CodeAction, Diagnostic, Range, TextEdit};
+use tree_sitter::Node;
+
+use crate::{
+    diagnostics::statement_analysis::{get_unsafe_variables, get_variables_in_statement},
+    document::DocumentData,
+    utils::{self, range::to_lsp_range},
+    workspace::program::Program,
+};
+
+use super::create_quick_fix;
+
+#[cfg(test)]
+use crate::test_utils::create_test_document;
+#[cfg(test)]
+use tower_lsp::lsp_types::{CodeActionOrCommand, NumberOrString};
+
+/**
+ * Offer to bind an unsafe variable with a domain atom in the body of its rule, or to replace it with _ if that keeps the rule safe
+ */
+pub fn get_fixes(
+    document: &DocumentData,
+    program: &Program,
+    diagnostic: &Diagnostic,
+) -> Vec<CodeAction> {
+    // The diagnostic may be outdated, only fix the variable if it is still there and unsafe
+    let variable = match utils::node::from_position(document, diagnostic.range.start) {
+        Some(node)
+            if node.kind() == "VARIABLE" && to_lsp_range(node.range()) == diagnostic.range =>
+        {
+            node
+        }
+        _ => return Vec::new(),
+    };
+    let statement = match get_ancestor(variable, "statement") {
+        Some(statement) => statement,
+        None => return Vec::new(),
+    };
+    let name = document.get_source_for_range(variable.range());
+    if !get_unsafe_variables(&statement, document).contains(&name) {
+        return Vec::new();
+    }
+
+    let mut actions = Vec::new();
+
+    if let Some((position, has_body)) = get_body_end(&statement) {
+        for atom in get_domain_atoms(document, program, &statement, &name) {
+            let text = if has_body {
+                format!(", {}", atom)
+            } else {
+                format!(" :- {}", atom)
+            };
+
+            actions.push(create_quick_fix(
+                document,
+                format!("Add '{}' to the body", atom),
+                TextEdit::new(Range::new(position, position), text),
+                diagnostic,
+                false,
+            ));
+        }
+    }
+
+    if can_anonymize(document, &statement, &variable, &name) {
+        actions.push(create_quick_fix(
+            document,
+            format!("Replace '{}' with '_'", name),
+            TextEdit::new(diagnostic.range, "_".to_string()),
+            diagnostic,
+            false,
+        ));
+    }
+
+    actions
+}
+
+/**
+ * Find atoms that can bind a variable. For every predicate argument the variable occurs in,
+ * other rules of the program are searched for a variable at the same argument of the same predicate.
+ * The positive body atoms that bind this variable there are the candidates, e.g. for 'color(X, red) :- not blocked(X).'
+ * the rule 'color(Y, blue) :- node(Y).' suggests node(X)
+ */
+fn get_domain_atoms(
+    document: &DocumentData,
+    program: &Program,
+    statement: &Node,
+    name: &str,
+) -> BTreeSet<String> {
+    let source = document.get_bytes();
+    let mut domain_atoms = BTreeSet::new();
+
+    for (_, _, variable) in get_variables_in_statement(statement, &source) {
+        if document.get_source_for_range(variable.range()) != name {
+            continue;
+        }
+        let (atom, index) = match get_argument_of_atom(&variable) {
+            Some(argument) => argument,
+            None => continue,
+        };
+
+        let predicate = (
+            document.get_source_for_range(atom.child(0).unwrap().range()),
+            get_arguments(&atom).len(),
+        );
+        let occurences = match program.predicate_semantics.predicates.get(&predicate) {
+            Some(occurences) => occurences,
+            None => continue,
+        };
+
+        for occurence in occurences.iter() {
+            let other_document = match program
+                .documents
+                .iter()
+                .find(|other| other.uri == occurence.uri)
+            {
+                Some(other_document) => other_document,
+                None => continue,
+            };
+            let other_atom = match get_atom(other_document, occurence.range) {
+                Some(other_atom) => other_atom,
+                None => continue,
+            };
+            let other_statement = match get_ancestor(other_atom, "statement") {
+                Some(other_statement) => other_statement,
+                None => continue,
+            };
+
+            // The rule of the unsafe variable can not tell how to bind it
+            if other_document.uri == document.uri && other_statement.id() == statement.id() {
+                continue;
+            }
+
+            let other_variable = match get_arguments(&other_atom).get(index) {
+                Some(term)
+                    if term.child_count() == 1 && term.child(0).unwrap().kind() == "VARIABLE" =>
+                {
+                    other_document.get_source_for_range(term.range())
+                }
+                _ => continue,
+            };
+
+            for (domain, domain_index) in get_binding_atoms(
+                other_document,
+                &other_statement,
+                &other_variable,
+                &other_atom,
+            ) {
+                domain_atoms.insert(render_atom(other_document, &domain, domain_index, name));
+            }
+        }
+    }
+
+    domain_atoms
+}
+
+/**
+ * Find the positive body atoms of a statement that have a variable as argument, together with the index of the argument
+ */
+fn get_binding_atoms<'a>(
+    document: &DocumentData,
+    statement: &Node<'a>,
+    name: &str,
+    except: &Node,
+) -> Vec<(Node<'a>, usize)> {
+    let body = match statement
+        .children(&mut statement.walk())
+        .find(|child| child.kind() == "bodydot")
+    {
+        Some(body) => body,
+        None => return Vec::new(),
+    };
+
+    let mut atoms = Vec::new();
+    let mut stack = vec![body];
+    while let Some(node) = stack.pop() {
+        for child in node.children(&mut node.walk()) {
+            match child.kind() {
+                "bodycomma" => stack.push(child),
+                // Only positive literals bind variables, classical negation is left out for simplicity
+                "literal" if child.child_count() == 1 => {
+                    let atom = child.child(0).unwrap();
+                    if atom.kind() != "atom"
+                        || atom.child(0).unwrap().kind() != "identifier"
+                        || atom.id() == except.id()
+                    {
+                        continue;
+                    }
+
+                    for (index, term) in get_arguments(&atom).iter().enumerate() {
+                        if term.child_count() == 1
+                            && term.child(0).unwrap().kind() == "VARIABLE"
+                            && document.get_source_for_range(term.range()) == name
+                        {
+                            atoms.push((atom, index));
+                        }
+                    }
+                }
+                _ => {}
+            }
+        }
+    }
+
+    atoms
+}
+
+/**
+ * Write an atom with the variable at one argument and _ at all others, e.g. edge(X,_)
+ */
+fn render_atom(document: &DocumentData, atom: &Node, index: usize, name: &str) -> String {
+    let identifier = document.get_source_for_range(atom.child(0).unwrap().range());
+    let arguments: Vec<&str> = (0..get_arguments(atom).len())
+        .map(|argument| if argument == index { name } else { "_" })
+        .collect();
+
+    if arguments.is_empty() {
+        return identifier;
+    }
+
+    format!("{}({})", identifier, arguments.join(","))
+}
+
+/**
+ * A variable can be replaced with _ if it occurs only once and is an argument of an atom outside of the head,
+ * e.g. 'a :- not b(X).'. Anonymous variables in the head or in comparisons are unsafe as well
+ */
+fn can_anonymize(document: &DocumentData, statement: &Node, variable: &Node, name: &str) -> bool {
+    let source = document.get_bytes();
+    let occurences = get_variables_in_statement(statement, &source)
+        .into_iter()
+        .filter(|(_, other, _)| *other == name)
+        .count();
+
+    occurences == 1
+        && get_argument_of_atom(variable).is_some()
+        && get_ancestor(*variable, "head").is_none()
+}
+
+/**
+ * Find where atoms are added to the body of a statement, it is also returned if the statement has a body yet
+ */
+fn get_body_end(statement: &Node) -> Option<(tower_lsp::lsp_types::Position, bool)> {
+    let last = statement.child(statement.child_count().checked_sub(1)?)?;
+
+    let (dot, has_body) = match last.kind() {
+        "bodydot" => (last.child(last.child_count().checked_sub(1)?)?, true),
+        "DOT" if statement.child(0)?.kind() == "head" => (last, false),
+        _ => return None,
+    };
+
+    if dot.kind() != "DOT" || dot.is_missing() {
+        return None;
+    }
+
+    Some((to_lsp_range(dot.range()).start, has_body))
+}
+
+/**
+ * Find the atom a variable is a direct argument of, together with the index of the argument
+ */
+fn get_argument_of_atom<'a>(variable: &Node<'a>) -> Option<(Node<'a>, usize)> {
+    let term = variable.parent()?;
+    if term.kind() != "term" || term.child_count() != 1 {
+        return None;
+    }
+
+    let mut node = term.parent()?;
+    while node.kind() == "termvec" {
+        node = node.parent()?;
+    }
+    if node.kind() != "argvec" {
+        return None;
+    }
+
+    let atom = node.parent()?;
+    if atom.kind() != "atom" || atom.child(0)?.kind() != "identifier" {
+        return None;
+    }
+
+    let index = get_arguments(&atom)
+        .iter()
+        .position(|argument| argument.id() == term.id())?;
+
+    Some((atom, index))
+}
+
+/**
+ * The arguments of an atom in order, atoms with pools like p(1;2) have none
+ */
+fn get_arguments<'a>(atom: &Node<'a>) -> Vec<Node<'a>> {
+    let mut arguments = Vec::new();
+
+    let argvec = match atom
+        .children(&mut atom.walk())
+        .find(|child| child.kind() == "argvec")
+    {
+        Some(argvec) if argvec.child_count() == 1 => argvec,
+        _ => return arguments,
+    };
+
+    collect_terms(argvec.child(0).unwrap(), &mut arguments);
+    arguments
+}
+
+fn collect_terms<'a>(termvec: Node<'a>, terms: &mut Vec<Node<'a>>) {
+    for child in termvec.children(&mut termvec.walk()) {
+        match child.kind() {
+            "termvec" => collect_terms(child, terms),
+            "term" => terms.push(child),
+            _ => {}
+        }
+    }
+}
+
+/**
+ * Find the atom of a predicate occurence, occurences of function symbols have none
+ */
+fn get_atom<'a>(document: &'a DocumentData, range: tree_sitter::Range) -> Option<Node<'a>> {
+    let mut node = document
+        .tree
+        .root_node()
+        .descendant_for_byte_range(range.start_byte, range.end_byte)?;
+
+    // Multiple nodes can span the same range, e.g. an atom only consisting of an identifier
+    while node.start_byte() == range.start_byte && node.end_byte() == range.end_byte {
+        if node.kind() == "atom" {
+            return Some(node);
+        }
+        node = node.parent()?;
+    }
+
+    None
+}
+
+fn get_ancestor<'a>(node: Node<'a>, kind: &str) -> Option<Node<'a>> {
+    let mut current = node.parent();
+    while let Some(parent) = current {
+        if parent.kind() == kind {
+            return Some(parent);
+        }
+        current = parent.parent();
+    }
+
+    None
+}
+
+#[cfg(test)]
+fn get_test_titles(source: &str) -> Vec<(String, String, usize)> {
+    use crate::diagnostics::{
+        diagnostic_codes::DiagnosticsCode, diagnostic_settings::DiagnosticSettings,
+        run_diagnostics,
+    };
+
+    let document = create_test_document(source.to_string());
+    let program = Program::new(vec![document.clone()]);
+    let diagnostics: Vec<Diagnostic> = run_diagnostics(
+        document.clone(),
+        &program,
+        &DiagnosticSettings::default(),
+        100,
+    )
+    .into_iter()
+    .filter(|diagnostic| {
+        diagnostic.code == Some(NumberOrString::Number(DiagnosticsCode::UnsafeVariable.into_i32()))
+    })
+    .collect();
+
+    super::handle(
+        &document,
+        &program,
+        &super::create_test_params(&document, diagnostics),
+    )
+    .unwrap()
+    .into_iter()
+    .map(|action| match action {
+        CodeActionOrCommand::CodeAction(action) => {
+            let edit = &action.edit.unwrap().changes.unwrap()[&document.uri][0];
+            (
+                action.title,
+                edit.new_text.clone(),
+                action.diagnostics.unwrap().len(),
+            )
+        }
+        CodeActionOrCommand::Command(command) => (command.title, String::new(), 0),
+    })
+    .collect()
+}
+
+#[test]
+fn domain_atoms_should_be_inferred_from_other_rules() {
+    let actions = get_test_titles(
+        "node(1..3). edge(1,2).\ncolor(X, red) :- node(X), not other(X).\ncolor(Y, blue) :- edge(_, Y).\nother(1).\ncolor(Z, green) :- not other(Z).",
+    );
+
+    assert_eq!(
+        actions,
+        vec![
+            (
+                "Add 'edge(_,Z)' to the body".to_string(),
+                ", edge(_,Z)".to_string(),
+                2
+            ),
+            (
+                "Add 'node(Z)' to the body".to_string(),
+                ", node(Z)".to_string(),
+                2
+            ),
+        ]
+    );
+}
+
+#[test]
+fn domain_atoms_should_be_added_to_facts_with_a_new_body() {
+    let actions = get_test_titles("p(X) :- q(X).\np(Y).");
+
+    assert_eq!(
+        actions,
+        vec![("Add 'q(Y)' to the body".to_string(), " :- q(Y)".to_string(), 1)]
+    );
+}
+
+#[test]
+fn fixes_should_be_offered_once_for_all_occurences_of_a_variable() {
+    let actions = get_test_titles("p(X) :- q(X).\np(Y) :- not r(Y).");
+
+    assert_eq!(
+        actions,
+        vec![("Add 'q(Y)' to the body".to_string(), ", q(Y)".to_string(), 2)]
+    );
+}
+
+#[test]
+fn unsafe_variables_should_only_be_anonymized_outside_of_the_head() {
+    let actions = get_test_titles("a :- not b(X).");
+    assert_eq!(
+        actions,
+        vec![("Replace 'X' with '_'".to_string(), "_".to_string(), 1)]
+    );
+
+    assert!(get_test_titles("a(X) :- b.").is_empty());
+    assert!(get_test_titles("a :- not b(X), X > 1.").is_empty());
+}
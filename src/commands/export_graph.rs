@@ -0,0 +1,480 @@
+use std::{
+    collections::{BTreeMap, BTreeSet},
+    ffi::OsStr,
+    path::{Path, PathBuf},
+};
+
+use dashmap::DashMap;
+use serde::Deserialize;
+use serde_json::{json, Value};
+use tower_lsp::lsp_types::Url;
+
+use crate::{
+    document::DocumentData,
+    workspace::{
+        dependency_graph::{DependencyGraph, Predicate},
+        program::Program,
+        Workspace,
+    },
+};
+
+#[cfg(test)]
+use std::str::FromStr;
+
+/**
+ * The argument of the export command, e.g. {"uri": "file:///encoding.lp", "format": "json", "cluster": "program"}
+ */
+#[derive(Debug, Deserialize)]
+pub struct ExportOptions {
+    /**
+     * The graph is built for the program of this document including all of its #include statements
+     */
+    pub uri: Url,
+
+    #[serde(default)]
+    pub format: ExportFormat,
+
+    #[serde(default)]
+    pub cluster: ClusterKind,
+
+    /**
+     * If set the graph is written to this file as well, it has to be inside of a workspace folder and end with .dot or .json
+     */
+    pub path: Option<PathBuf>,
+}
+
+#[derive(Clone, Copy, Debug, Default, Deserialize, Eq, PartialEq)]
+#[serde(rename_all = "lowercase")]
+pub enum ExportFormat {
+    #[default]
+    Dot,
+    Json,
+}
+
+/**
+ * How predicates are grouped, either by the file or by the #program part they are first defined in
+ */
+#[derive(Clone, Copy, Debug, Default, Deserialize, Eq, PartialEq)]
+#[serde(rename_all = "lowercase")]
+pub enum ClusterKind {
+    #[default]
+    File,
+    Program,
+}
+
+/**
+ * Export the dependency graph of a program, DOT is returned as a string and JSON as an object
+ */
+pub fn handle(
+    workspace: &Workspace,
+    open_documents: &DashMap<String, DocumentData>,
+    arguments: &[Value],
+) -> Result<Value, String> {
+    let options: ExportOptions = match arguments.first() {
+        Some(argument) => serde_json::from_value(argument.clone())
+            .map_err(|error| format!("Invalid arguments: {}", error))?,
+        None => return Err("Expected the uri of a document as argument".to_string()),
+    };
+
+    let program = workspace.get_program(&options.uri, open_documents);
+    let output = export(&program, options.format, options.cluster);
+
+    if let Some(path) = options.path {
+        let path = check_export_path(workspace, &path, options.format)?;
+        let content = match &output {
+            Value::String(dot) => dot.clone(),
+            json => serde_json::to_string_pretty(json).unwrap(),
+        };
+        std::fs::write(&path, content)
+            .map_err(|error| format!("Could not write {}: {}", path.display(), error))?;
+    }
+
+    Ok(output)
+}
+
+/**
+ * The client may only write files with the extension of the format into a workspace folder, never anywhere else on disk.
+ * Returns the path with all symbolic links of its folder resolved
+ */
+fn check_export_path(
+    workspace: &Workspace,
+    path: &Path,
+    format: ExportFormat,
+) -> Result<PathBuf, String> {
+    let extension = match format {
+        ExportFormat::Dot => "dot",
+        ExportFormat::Json => "json",
+    };
+    if path.extension() != Some(OsStr::new(extension)) {
+        return Err(format!(
+            "The graph can only be written to a .{} file",
+            extension
+        ));
+    }
+
+    let (folder, file_name) = match (path.parent(), path.file_name()) {
+        (Some(folder), Some(file_name)) if path.is_absolute() => (folder, file_name),
+        _ => return Err(format!("{} is not an absolute path", path.display())),
+    };
+    let target = folder
+        .canonicalize()
+        .map_err(|error| format!("Could not write {}: {}", path.display(), error))?
+        .join(file_name);
+
+    // A link in the workspace could point to any file
+    if target
+        .symlink_metadata()
+        .is_ok_and(|metadata| metadata.file_type().is_symlink())
+    {
+        return Err(format!("{} is a symbolic link", path.display()));
+    }
+
+    let is_in_workspace = workspace
+        .get_folders()
+        .iter()
+        .filter_map(|folder| folder.canonicalize().ok())
+        .any(|folder| target.starts_with(folder));
+    if !is_in_workspace {
+        return Err(format!(
+            "{} is not inside of a workspace folder",
+            path.display()
+        ));
+    }
+
+    Ok(target)
+}
+
+/**
+ * Build the dependency graph of a program and convert it into the requested format
+ */
+pub fn export(program: &Program, format: ExportFormat, cluster: ClusterKind) -> Value {
+    let graph = DependencyGraph::new(program);
+    let clusters = get_clusters(&graph, cluster);
+    let names = get_cluster_names(&clusters, cluster);
+
+    match format {
+        ExportFormat::Dot => Value::String(to_dot(&graph, &clusters, &names)),
+        ExportFormat::Json => to_json(&graph, &clusters, &names),
+    }
+}
+
+/**
+ * Group the predicates by the place of their first definition, predicates that are never defined belong to no cluster
+ */
+fn get_clusters(graph: &DependencyGraph, cluster: ClusterKind) -> BTreeMap<String, Vec<Predicate>> {
+    let mut assigned = BTreeSet::new();
+    let mut clusters: BTreeMap<String, Vec<Predicate>> = BTreeMap::new();
+
+    for definition in &graph.definitions {
+        if !assigned.insert(definition.predicate.clone()) {
+            continue;
+        }
+
+        // Files are told apart by their uri, different folders can contain files with the same name
+        let key = match cluster {
+            ClusterKind::File => definition.uri.to_string(),
+            ClusterKind::Program => definition.part.clone(),
+        };
+        clusters
+            .entry(key)
+            .or_default()
+            .push(definition.predicate.clone());
+    }
+
+    for predicates in clusters.values_mut() {
+        predicates.sort();
+    }
+
+    clusters
+}
+
+/**
+ * Get the names shown for the clusters. Files are named by their file name, unless another file has the same name
+ */
+fn get_cluster_names(
+    clusters: &BTreeMap<String, Vec<Predicate>>,
+    cluster: ClusterKind,
+) -> BTreeMap<String, String> {
+    let names: BTreeMap<String, String> = clusters
+        .keys()
+        .map(|key| {
+            let name = match (cluster, Url::parse(key)) {
+                (ClusterKind::File, Ok(uri)) => get_file_name(&uri),
+                _ => key.clone(),
+            };
+            (key.clone(), name)
+        })
+        .collect();
+
+    names
+        .iter()
+        .map(|(key, name)| {
+            let is_ambiguous = names.values().filter(|other| *other == name).count() > 1;
+            let name = match Url::parse(key) {
+                Ok(uri) if is_ambiguous => uri.path().to_string(),
+                _ => name.clone(),
+            };
+            (key.clone(), name)
+        })
+        .collect()
+}
+
+fn get_file_name(uri: &Url) -> String {
+    uri.path_segments()
+        .and_then(|mut segments| segments.next_back())
+        .filter(|name| !name.is_empty())
+        .map(|name| name.to_string())
+        .unwrap_or_else(|| uri.to_string())
+}
+
+/**
+ * The distinct edges of the graph, multiple rules can add the same dependency
+ */
+fn get_edges(graph: &DependencyGraph) -> BTreeSet<(&Predicate, &Predicate, bool, bool)> {
+    graph
+        .dependencies
+        .iter()
+        .map(|dependency| {
+            (
+                &dependency.head,
+                &dependency.body,
+                dependency.negative,
+                dependency.aggregate,
+            )
+        })
+        .collect()
+}
+
+fn get_label(negative: bool, aggregate: bool) -> &'static str {
+    match (negative, aggregate) {
+        (false, false) => "positive",
+        (true, false) => "negative",
+        (false, true) => "aggregate",
+        (true, true) => "negative aggregate",
+    }
+}
+
+fn get_id(predicate: &Predicate) -> String {
+    format!("{}/{}", predicate.0, predicate.1)
+}
+
+/**
+ * Write the graph in the Graphviz DOT language, negative edges are dashed
+ */
+fn to_dot(
+    graph: &DependencyGraph,
+    clusters: &BTreeMap<String, Vec<Predicate>>,
+    names: &BTreeMap<String, String>,
+) -> String {
+    let mut dot = "digraph dependencies {\n    node [shape=box];\n".to_string();
+    let mut clustered = BTreeSet::new();
+
+    for (index, (key, predicates)) in clusters.iter().enumerate() {
+        dot.push_str(&format!(
+            "    subgraph cluster_{} {{\n        label=\"{}\";\n",
+            index,
+            names[key].replace('"', "\\\"")
+        ));
+        for predicate in predicates {
+            dot.push_str(&format!("        \"{}\";\n", get_id(predicate)));
+            clustered.insert(predicate);
+        }
+        dot.push_str("    }\n");
+    }
+
+    for predicate in graph
+        .predicates
+        .iter()
+        .filter(|predicate| !clustered.contains(predicate))
+    {
+        dot.push_str(&format!("    \"{}\";\n", get_id(predicate)));
+    }
+
+    for (head, body, negative, aggregate) in get_edges(graph) {
+        let style = if negative { ", style=dashed" } else { "" };
+        dot.push_str(&format!(
+            "    \"{}\" -> \"{}\" [label=\"{}\"{}];\n",
+            get_id(head),
+            get_id(body),
+            get_label(negative, aggregate),
+            style
+        ));
+    }
+
+    dot.push('}');
+    dot
+}
+
+/**
+ * Write the graph as JSON with a list of nodes, edges and clusters
+ */
+fn to_json(
+    graph: &DependencyGraph,
+    clusters: &BTreeMap<String, Vec<Predicate>>,
+    names: &BTreeMap<String, String>,
+) -> Value {
+    let mut cluster_of = BTreeMap::new();
+    for (key, predicates) in clusters {
+        for predicate in predicates {
+            cluster_of.insert(predicate, key);
+        }
+    }
+
+    let nodes: Vec<Value> = graph
+        .predicates
+        .iter()
+        .map(|predicate| {
+            json!({
+                "id": get_id(predicate),
+                "name": predicate.0,
+                "arity": predicate.1,
+                "cluster": cluster_of.get(predicate),
+            })
+        })
+        .collect();
+
+    let edges: Vec<Value> = get_edges(graph)
+        .into_iter()
+        .map(|(head, body, negative, aggregate)| {
+            json!({
+                "source": get_id(head),
+                "target": get_id(body),
+                "label": get_label(negative, aggregate),
+                "negative": negative,
+                "aggregate": aggregate,
+            })
+        })
+        .collect();
+
+    let clusters: Vec<Value> = clusters
+        .iter()
+        .map(|(key, predicates)| {
+            json!({
+                "id": key,
+                "name": names[key],
+                "nodes": predicates.iter().map(get_id).collect::<Vec<String>>(),
+            })
+        })
+        .collect();
+
+    json!({
+        "nodes": nodes,
+        "edges": edges,
+        "clusters": clusters,
+    })
+}
+
+#[cfg(test)]
+fn get_test_program() -> Program {
+    let document = DocumentData::from_source(
+        Url::from_str("file:///encoding.lp").unwrap(),
+        "reach(X) :- start(X).\nreach(Y) :- reach(X), edge(X,Y).\n#program check.\nunreached(X) :- node(X), not reach(X).",
+        1,
+    );
+    let instance = DocumentData::from_source(
+        Url::from_str("file:///instance.lp").unwrap(),
+        "node(1). edge(1,2). start(1).",
+        1,
+    );
+    Program::new(vec![document, instance])
+}
+
+#[test]
+fn graph_should_be_exported_as_dot_clustered_by_file() {
+    let dot = export(&get_test_program(), ExportFormat::Dot, ClusterKind::File);
+
+    assert_eq!(
+        dot.as_str().unwrap(),
+        "digraph dependencies {
+    node [shape=box];
+    subgraph cluster_0 {
+        label=\"encoding.lp\";
+        \"reach/1\";
+        \"unreached/1\";
+    }
+    subgraph cluster_1 {
+        label=\"instance.lp\";
+        \"edge/2\";
+        \"node/1\";
+        \"start/1\";
+    }
+    \"reach/1\" -> \"edge/2\" [label=\"positive\"];
+    \"reach/1\" -> \"reach/1\" [label=\"positive\"];
+    \"reach/1\" -> \"start/1\" [label=\"positive\"];
+    \"unreached/1\" -> \"node/1\" [label=\"positive\"];
+    \"unreached/1\" -> \"reach/1\" [label=\"negative\", style=dashed];
+}"
+    );
+}
+
+#[test]
+fn graph_should_be_exported_as_json_clustered_by_program_part() {
+    let json = export(
+        &get_test_program(),
+        ExportFormat::Json,
+        ClusterKind::Program,
+    );
+
+    assert_eq!(json["nodes"].as_array().unwrap().len(), 5);
+    assert_eq!(json["nodes"][4]["id"], "unreached/1");
+    assert_eq!(json["nodes"][4]["cluster"], "check");
+    assert_eq!(json["edges"][4]["source"], "unreached/1");
+    assert_eq!(json["edges"][4]["target"], "reach/1");
+    assert_eq!(json["edges"][4]["label"], "negative");
+    assert_eq!(
+        json["clusters"],
+        json!([
+            {"id": "base", "name": "base", "nodes": ["edge/2", "node/1", "reach/1", "start/1"]},
+            {"id": "check", "name": "check", "nodes": ["unreached/1"]},
+        ])
+    );
+}
+
+#[test]
+fn files_with_the_same_name_should_be_separate_clusters() {
+    let program = Program::new(vec![
+        DocumentData::from_source(Url::from_str("file:///a/base.lp").unwrap(), "p.", 1),
+        DocumentData::from_source(Url::from_str("file:///b/base.lp").unwrap(), "q.", 1),
+        DocumentData::from_source(Url::from_str("file:///c/other.lp").unwrap(), "r.", 1),
+    ]);
+
+    let json = export(&program, ExportFormat::Json, ClusterKind::File);
+
+    assert_eq!(
+        json["clusters"],
+        json!([
+            {"id": "file:///a/base.lp", "name": "/a/base.lp", "nodes": ["p/0"]},
+            {"id": "file:///b/base.lp", "name": "/b/base.lp", "nodes": ["q/0"]},
+            {"id": "file:///c/other.lp", "name": "other.lp", "nodes": ["r/0"]},
+        ])
+    );
+    assert_eq!(json["nodes"][0]["cluster"], "file:///a/base.lp");
+}
+
+#[test]
+fn graphs_should_only_be_written_into_workspace_folders() {
+    let directory = std::env::temp_dir().join("asp_lsp_export_graph");
+    std::fs::create_dir_all(directory.join("project")).unwrap();
+    std::fs::write(directory.join("project").join("encoding.lp"), "a :- b. b.").unwrap();
+
+    let workspace = Workspace::new();
+    workspace.set_folders(vec![Url::from_file_path(directory.join("project")).unwrap()]);
+    let export_to = |path: PathBuf| {
+        handle(
+            &workspace,
+            &DashMap::new(),
+            &[json!({
+                "uri": Url::from_file_path(directory.join("project").join("encoding.lp")).unwrap(),
+                "path": path,
+            })],
+        )
+    };
+
+    assert!(export_to(directory.join("project").join("graph.dot")).is_ok());
+    assert!(directory.join("project").join("graph.dot").exists());
+
+    assert!(export_to(directory.join("graph.dot")).is_err());
+    assert!(export_to(directory.join("project").join("..").join("graph.dot")).is_err());
+    assert!(export_to(directory.join("project").join("encoding.lp")).is_err());
+    assert!(export_to(PathBuf::from("graph.dot")).is_err());
+}
@@ -0,0 +1,35 @@
+use dashmap::DashMap;
+use serde_json::Value;
+use tower_lsp::lsp_types::ExecuteCommandParams;
+
+use crate::{document::DocumentData, workspace::Workspace};
+
+pub mod export_graph;
+
+/**
+ * Export the predicate dependency graph of a program as Graphviz DOT or JSON
+ */
+pub const EXPORT_DEPENDENCY_GRAPH: &str = "asp.exportDependencyGraph";
+
+/**
+ * The commands the client can execute with workspace/executeCommand
+ */
+pub fn get_commands() -> Vec<String> {
+    vec![EXPORT_DEPENDENCY_GRAPH.to_string()]
+}
+
+/**
+ * Execute a command, if the command or its arguments are unknown an error message is returned
+ */
+pub fn handle(
+    workspace: &Workspace,
+    open_documents: &DashMap<String, DocumentData>,
+    params: &ExecuteCommandParams,
+) -> Result<Option<Value>, String> {
+    match params.command.as_str() {
+        EXPORT_DEPENDENCY_GRAPH => {
+            export_graph::handle(workspace, open_documents, &params.arguments).map(Some)
+        }
+        _ => Err(format!("Unknown command '{}'", params.command)),
+    }
+}
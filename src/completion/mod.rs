@@ -1,7 +1,7 @@
 use tower_lsp::lsp_types::{CompletionContext, CompletionItem, CompletionTriggerKind, Position};
 use tree_sitter::{Node, Point};
 
-use crate::document::DocumentData;
+use crate::{document::DocumentData, workspace::program::Program};
 
 use self::{
     keyword_competion::keyword_completion_resolver,
@@ -17,6 +17,7 @@ mod predicate_completion;
  */
 pub fn check_completion(
     document: &DocumentData,
+    program: &Program,
     context: CompletionContext,
     trigger_character: String,
     position: Position,
@@ -41,7 +42,7 @@ pub fn check_completion(
     if trigger_character == "#" {
         return keyword_completion_resolver(node);
     } else if context.trigger_kind == CompletionTriggerKind::INVOKED {
-        return predicate_completion_resolver(document, node);
+        return predicate_completion_resolver(document, program, node);
     }
 
     None
@@ -1,13 +1,14 @@
 use tower_lsp::lsp_types::{CompletionItem, CompletionItemKind, Documentation, InsertTextFormat};
 use tree_sitter::Node;
 
-use crate::document::DocumentData;
+use crate::{document::DocumentData, workspace::program::Program};
 
 /**
- * Resolve a keyword completion
+ * Resolve a predicate completion, predicates of included files are suggested as well
  */
 pub fn predicate_completion_resolver(
     document: &DocumentData,
+    program: &Program,
     node: Option<Node>,
 ) -> Option<Vec<CompletionItem>> {
     let mut items = Vec::new();
@@ -37,10 +38,8 @@ pub fn predicate_completion_resolver(
             is_show_statement = parent.unwrap().child(0).unwrap().utf8_text(&document.get_bytes()).unwrap() == "#show";
         }
 
-        // Give a suggestion for each atom in the document
-        for ((identifier, arity), occurences) in
-            document.semantics.predicate_semantics.predicates.clone()
-        {
+        // Give a suggestion for each atom in the program
+        for ((identifier, arity), occurences) in program.predicate_semantics.predicates.clone() {
             let mut insert_text_snippet = identifier.clone();
 
             // Check if this predicate isn't at the current location of the cursor, if it is we do not suggest this predicate
@@ -0,0 +1,200 @@
+use std::collections::HashSet;
+
+use tower_lsp::lsp_types::{DiagnosticRelatedInformation, DiagnosticSeverity, Location};
+
+use crate::{
+    document::DocumentData,
+    utils::range::to_lsp_range,
+    workspace::{
+        dependency_graph::{Dependency, DependencyGraph},
+        program::Program,
+    },
+};
+
+#[cfg(test)]
+use crate::test_utils::create_test_document;
+
+use super::{diagnostic_codes::DiagnosticsCode, diagnostic_run_data::DiagnosticsRunData};
+
+/**
+ * Report negative dependencies of the document that are part of a cycle in the predicate dependency graph.
+ * Cycles through an odd number of negations are reported as odd loops, all others as non-stratified negation
+ */
+pub fn negation_cycle_analysis(
+    diagnostic_data: &mut DiagnosticsRunData,
+    document: &DocumentData,
+    program: &Program,
+    non_stratified_severity: Option<DiagnosticSeverity>,
+    odd_loop_severity: Option<DiagnosticSeverity>,
+) {
+    let graph = DependencyGraph::new(program);
+    let component_ids = graph.get_component_ids();
+
+    let mut dependencies: Vec<&Dependency> = graph
+        .dependencies
+        .iter()
+        .filter(|dependency| {
+            dependency.negative
+                && dependency.uri == document.uri
+                && component_ids.get(&dependency.head) == component_ids.get(&dependency.body)
+        })
+        .collect();
+    dependencies.sort_by_key(|dependency| dependency.range.start_byte);
+
+    // A literal in a rule with multiple heads is a dependency of every head, report it only once
+    let mut reported = HashSet::new();
+
+    for dependency in dependencies {
+        //If we reached the error limit stop analyzing further
+        if diagnostic_data.current_number_of_problems >= diagnostic_data.maximum_number_of_problems
+        {
+            return;
+        }
+
+        if reported.contains(&dependency.range.start_byte) {
+            continue;
+        }
+
+        let odd_loop = odd_loop_severity
+            .and_then(|severity| Some((severity, graph.find_cycle(dependency, true)?)));
+        let (severity, cycle, code, message) = if let Some((severity, cycle)) = odd_loop {
+            (
+                severity,
+                cycle,
+                DiagnosticsCode::OddLoop,
+                "is part of an odd loop through negation",
+            )
+        } else if let Some(severity) = non_stratified_severity {
+            match graph.find_cycle(dependency, false) {
+                Some(cycle) => (
+                    severity,
+                    cycle,
+                    DiagnosticsCode::NonStratifiedNegation,
+                    "is negated in a cycle, the program is not stratified",
+                ),
+                None => continue,
+            }
+        } else {
+            continue;
+        };
+
+        reported.insert(dependency.range.start_byte);
+        diagnostic_data.create_linter_diagnostic_with_related_information(
+            dependency.range,
+            severity,
+            code.into_i32(),
+            format!(
+                "'{}/{}' {}: {}",
+                dependency.body.0,
+                dependency.body.1,
+                message,
+                format_cycle(&cycle)
+            ),
+            get_related_information(&cycle),
+        );
+    }
+}
+
+/**
+ * Write a cycle as a chain of predicates, e.g. a/0 -> not b/0 -> a/0
+ */
+fn format_cycle(cycle: &[&Dependency]) -> String {
+    let mut chain = format!("{}/{}", cycle[0].head.0, cycle[0].head.1);
+
+    for dependency in cycle {
+        chain.push_str(" -> ");
+        if dependency.negative {
+            chain.push_str("not ");
+        }
+        chain.push_str(&format!("{}/{}", dependency.body.0, dependency.body.1));
+    }
+
+    chain
+}
+
+/**
+ * Point to the places where the other dependencies of the cycle occur
+ */
+fn get_related_information(cycle: &[&Dependency]) -> Vec<DiagnosticRelatedInformation> {
+    cycle
+        .iter()
+        .skip(1)
+        .map(|dependency| DiagnosticRelatedInformation {
+            location: Location::new(dependency.uri.clone(), to_lsp_range(dependency.range)),
+            message: format!(
+                "'{}/{}' depends on '{}/{}' here",
+                dependency.head.0, dependency.head.1, dependency.body.0, dependency.body.1
+            ),
+        })
+        .collect()
+}
+
+#[cfg(test)]
+fn get_test_messages(source: &str) -> Vec<(i32, String)> {
+    use tower_lsp::lsp_types::NumberOrString;
+
+    let document = create_test_document(source.to_string());
+    let program = Program::new(vec![document.clone()]);
+
+    let mut diags = DiagnosticsRunData::create_test_diagnostics();
+    negation_cycle_analysis(
+        &mut diags,
+        &document,
+        &program,
+        Some(DiagnosticSeverity::INFORMATION),
+        Some(DiagnosticSeverity::INFORMATION),
+    );
+
+    diags
+        .total_diagnostics
+        .into_iter()
+        .map(|diagnostic| match diagnostic.code {
+            Some(NumberOrString::Number(code)) => (code, diagnostic.message),
+            _ => panic!("Expected a numeric code"),
+        })
+        .collect()
+}
+
+#[test]
+fn even_negation_cycles_should_not_be_stratified() {
+    assert_eq!(
+        get_test_messages("a :- not b. b :- not a. c :- not a."),
+        vec![
+            (
+                DiagnosticsCode::NonStratifiedNegation.into_i32(),
+                "'b/0' is negated in a cycle, the program is not stratified: a/0 -> not b/0 -> not a/0"
+                    .to_string()
+            ),
+            (
+                DiagnosticsCode::NonStratifiedNegation.into_i32(),
+                "'a/0' is negated in a cycle, the program is not stratified: b/0 -> not a/0 -> not b/0"
+                    .to_string()
+            ),
+        ]
+    );
+}
+
+#[test]
+fn odd_loops_should_be_detected() {
+    assert_eq!(
+        get_test_messages("p(X) :- q(X), not r(X). r(X) :- p(X). s :- not s."),
+        vec![
+            (
+                DiagnosticsCode::OddLoop.into_i32(),
+                "'r/1' is part of an odd loop through negation: p/1 -> not r/1 -> p/1".to_string()
+            ),
+            (
+                DiagnosticsCode::OddLoop.into_i32(),
+                "'s/0' is part of an odd loop through negation: s/0 -> not s/0".to_string()
+            ),
+        ]
+    );
+}
+
+#[test]
+fn stratified_programs_should_not_be_reported() {
+    assert!(get_test_messages(
+        "reach(X,Y) :- edge(X,Y). reach(X,Z) :- reach(X,Y), edge(Y,Z).\nunreachable(X,Y) :- node(X), node(Y), not reach(X,Y)."
+    )
+    .is_empty());
+}
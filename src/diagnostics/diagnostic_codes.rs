@@ -1,6 +1,7 @@
 /**
  * DIAGNOSTICS CODES TREE-SITTER
  */
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
 pub enum DiagnosticsCode {
     /**
      * ERROR CODES TREE SITTER
@@ -15,10 +16,134 @@ pub enum DiagnosticsCode {
      * ERROR CODES ANALYSIS
      */
     UnsafeVariable = 2000,
+    UndefinedPredicate = 2001,
+    ArityMismatch = 2002,
+    UnusedPredicate = 2003,
+    UnmatchedShow = 2004,
+    SingletonVariable = 2005,
+    NonStratifiedNegation = 2006,
+    OddLoop = 2007,
+    UnknownSuppression = 2008,
+    UnusedSuppression = 2009,
 }
 
 impl DiagnosticsCode {
+    pub const ALL: [DiagnosticsCode; 13] = [
+        DiagnosticsCode::UnknownParseState,
+        DiagnosticsCode::ExpectedDot,
+        DiagnosticsCode::ExpectedMissingToken,
+        DiagnosticsCode::UnsafeVariable,
+        DiagnosticsCode::UndefinedPredicate,
+        DiagnosticsCode::ArityMismatch,
+        DiagnosticsCode::UnusedPredicate,
+        DiagnosticsCode::UnmatchedShow,
+        DiagnosticsCode::SingletonVariable,
+        DiagnosticsCode::NonStratifiedNegation,
+        DiagnosticsCode::OddLoop,
+        DiagnosticsCode::UnknownSuppression,
+        DiagnosticsCode::UnusedSuppression,
+    ];
+
     pub fn into_i32(self) -> i32 {
         self as i32
     }
+
+    /**
+     * Find the diagnostics code for the number of a diagnostic
+     */
+    pub fn from_i32(code: i32) -> Option<DiagnosticsCode> {
+        DiagnosticsCode::ALL
+            .into_iter()
+            .find(|diagnostics_code| diagnostics_code.into_i32() == code)
+    }
+
+    /**
+     * The name of the code in suppression comments, e.g. unsafe-variable for UnsafeVariable
+     */
+    pub fn get_name(self) -> String {
+        let mut name = String::new();
+
+        for character in self.get_rule_id().chars() {
+            if character.is_uppercase() && !name.is_empty() {
+                name.push('-');
+            }
+            name.push(character.to_ascii_lowercase());
+        }
+
+        name
+    }
+
+    /**
+     * Only the diagnostics of the analyses can be suppressed, syntax errors and the suppressions themselves can not
+     */
+    pub fn is_suppressible(self) -> bool {
+        !matches!(
+            self,
+            DiagnosticsCode::UnknownParseState
+                | DiagnosticsCode::ExpectedDot
+                | DiagnosticsCode::ExpectedMissingToken
+                | DiagnosticsCode::UnknownSuppression
+                | DiagnosticsCode::UnusedSuppression
+        )
+    }
+
+    /**
+     * A stable name for the code that tools outside of the editor can use to identify a rule
+     */
+    pub fn get_rule_id(self) -> &'static str {
+        match self {
+            DiagnosticsCode::UnknownParseState => "UnknownParseState",
+            DiagnosticsCode::ExpectedDot => "ExpectedDot",
+            DiagnosticsCode::ExpectedMissingToken => "ExpectedMissingToken",
+            DiagnosticsCode::UnsafeVariable => "UnsafeVariable",
+            DiagnosticsCode::UndefinedPredicate => "UndefinedPredicate",
+            DiagnosticsCode::ArityMismatch => "ArityMismatch",
+            DiagnosticsCode::UnusedPredicate => "UnusedPredicate",
+            DiagnosticsCode::UnmatchedShow => "UnmatchedShow",
+            DiagnosticsCode::SingletonVariable => "SingletonVariable",
+            DiagnosticsCode::NonStratifiedNegation => "NonStratifiedNegation",
+            DiagnosticsCode::OddLoop => "OddLoop",
+            DiagnosticsCode::UnknownSuppression => "UnknownSuppression",
+            DiagnosticsCode::UnusedSuppression => "UnusedSuppression",
+        }
+    }
+
+    /**
+     * A short description of what the rule checks
+     */
+    pub fn get_description(self) -> &'static str {
+        match self {
+            DiagnosticsCode::UnknownParseState => "The encoding could not be parsed",
+            DiagnosticsCode::ExpectedDot => "A statement is not terminated by a dot",
+            DiagnosticsCode::ExpectedMissingToken => "A token is missing in the encoding",
+            DiagnosticsCode::UnsafeVariable => "A variable is not bound by a positive body literal",
+            DiagnosticsCode::UndefinedPredicate => {
+                "A predicate is used but never occurs in a head, so it can never be true"
+            }
+            DiagnosticsCode::ArityMismatch => {
+                "A predicate only occurs in bodies, but its name is defined with another arity"
+            }
+            DiagnosticsCode::UnusedPredicate => {
+                "A predicate is derived, but neither used in a body nor shown"
+            }
+            DiagnosticsCode::UnmatchedShow => {
+                "A #show statement refers to a predicate that does not occur in the program"
+            }
+            DiagnosticsCode::SingletonVariable => {
+                "A named variable occurs only once in a statement and should be written as _"
+            }
+            DiagnosticsCode::NonStratifiedNegation => {
+                "A predicate depends on itself through default negation, so the program is not stratified"
+            }
+            DiagnosticsCode::OddLoop => {
+                "A predicate depends on itself through an odd number of negations"
+            }
+            DiagnosticsCode::UnknownSuppression => {
+                "A suppression comment names a rule that does not exist or can not be suppressed"
+            }
+            DiagnosticsCode::UnusedSuppression => {
+                "A suppression comment does not suppress any diagnostic"
+            }
+        }
+    }
 }
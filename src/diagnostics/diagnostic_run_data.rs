@@ -1,4 +1,14 @@
-use tower_lsp::lsp_types::{Diagnostic, DiagnosticSeverity, Position, Range};
+use std::collections::HashMap;
+
+use serde_json::Value;
+use tower_lsp::lsp_types::{
+    Diagnostic, DiagnosticRelatedInformation, DiagnosticSeverity, Position, Range,
+};
+
+use super::{
+    diagnostic_codes::DiagnosticsCode, diagnostic_settings::DiagnosticLevel,
+    suppression::Suppression,
+};
 
 /**
  * A object that contains all the diagnostic data which was found
@@ -10,6 +20,12 @@ pub struct DiagnosticsRunData {
 
     //A list of diagnostics to be send to the user
     pub total_diagnostics: Vec<Diagnostic>,
+
+    //The levels codes are reported with instead of their own severity
+    pub overrides: HashMap<i32, DiagnosticLevel>,
+
+    //The rules that are disabled by comments in the document
+    pub suppressions: Vec<Suppression>,
 }
 
 impl DiagnosticsRunData {
@@ -23,6 +39,46 @@ impl DiagnosticsRunData {
         code_number: i32,
         message: String,
     ) {
+        self.push_linter_diagnostic(range, severity, code_number, message);
+    }
+
+    /**
+     * Create a diagnostic message from clinlint that points to other locations in the program
+     */
+    pub fn create_linter_diagnostic_with_related_information(
+        &mut self,
+        range: tree_sitter::Range,
+        severity: DiagnosticSeverity,
+        code_number: i32,
+        message: String,
+        related_information: Vec<DiagnosticRelatedInformation>,
+    ) {
+        if self.push_linter_diagnostic(range, severity, code_number, message) {
+            if let Some(diagnostic) = self.total_diagnostics.last_mut() {
+                diagnostic.related_information = Some(related_information);
+            }
+        }
+    }
+
+    /**
+     * Create a diagnostic message from clinlint unless it is suppressed by a comment, returns if it was created
+     */
+    fn push_linter_diagnostic(
+        &mut self,
+        range: tree_sitter::Range,
+        severity: DiagnosticSeverity,
+        code_number: i32,
+        message: String,
+    ) -> bool {
+        let suppression = self
+            .suppressions
+            .iter_mut()
+            .find(|suppression| suppression.matches(code_number, range.start_point.row));
+        if let Some(suppression) = suppression {
+            suppression.used = true;
+            return false;
+        }
+
         self.create_diagnostic(
             range,
             severity,
@@ -32,6 +88,55 @@ impl DiagnosticsRunData {
         )
     }
 
+    /**
+     * Report suppression comments with unknown rules and the ones that did not suppress anything.
+     * A suppression of a rule that is not enabled is never reported as unused
+     */
+    pub fn report_suppressions(&mut self, is_enabled: impl Fn(DiagnosticsCode) -> bool) {
+        // If the analysis stopped early, a suppression could have been needed later on
+        let stopped_early = self.current_number_of_problems >= self.maximum_number_of_problems;
+
+        for suppression in std::mem::take(&mut self.suppressions) {
+            if self.current_number_of_problems >= self.maximum_number_of_problems {
+                return;
+            }
+
+            match suppression.code {
+                None if suppression.rule.is_empty() => {
+                    self.create_linter_diagnostic(
+                        suppression.range,
+                        DiagnosticSeverity::WARNING,
+                        DiagnosticsCode::UnknownSuppression.into_i32(),
+                        format!(
+                            "'{0}' needs the rules to disable, e.g. {0} unsafe-variable",
+                            suppression.get_directive()
+                        ),
+                    );
+                }
+                None => {
+                    self.create_linter_diagnostic(
+                        suppression.range,
+                        DiagnosticSeverity::WARNING,
+                        DiagnosticsCode::UnknownSuppression.into_i32(),
+                        format!("'{}' is not a rule that can be disabled", suppression.rule),
+                    );
+                }
+                Some(code) if !suppression.used && !stopped_early && is_enabled(code) => {
+                    self.create_linter_diagnostic(
+                        suppression.range,
+                        DiagnosticSeverity::WARNING,
+                        DiagnosticsCode::UnusedSuppression.into_i32(),
+                        format!(
+                            "'{}' is disabled, but nothing is suppressed",
+                            suppression.rule
+                        ),
+                    );
+                }
+                _ => {}
+            }
+        }
+    }
+
     /**
      * Create a diagnostic message from tree-sitter
      */
@@ -48,7 +153,31 @@ impl DiagnosticsRunData {
             code_number,
             "tree-sitter".to_string(),
             message,
-        )
+        );
+    }
+
+    /**
+     * Create a diagnostic message from tree-sitter with data that is needed to compute a quick fix for it
+     */
+    pub fn create_tree_sitter_diagnostic_with_data(
+        &mut self,
+        range: tree_sitter::Range,
+        severity: DiagnosticSeverity,
+        code_number: i32,
+        message: String,
+        data: Value,
+    ) {
+        if self.create_diagnostic(
+            range,
+            severity,
+            code_number,
+            "tree-sitter".to_string(),
+            message,
+        ) {
+            if let Some(diagnostic) = self.total_diagnostics.last_mut() {
+                diagnostic.data = Some(data);
+            }
+        }
     }
 
     /**
@@ -61,7 +190,15 @@ impl DiagnosticsRunData {
         code_number: i32,
         source: String,
         message: String,
-    ) {
+    ) -> bool {
+        let severity = match self.overrides.get(&code_number) {
+            Some(level) => match level.get_severity() {
+                Some(severity) => severity,
+                None => return false,
+            },
+            None => severity,
+        };
+
         self.total_diagnostics
             .push(Diagnostic::new_with_code_number(
                 Range::new(
@@ -80,6 +217,7 @@ impl DiagnosticsRunData {
                 message,
             ));
         self.current_number_of_problems += 1;
+        true
     }
 
     #[cfg(test)]
@@ -88,6 +226,8 @@ impl DiagnosticsRunData {
             maximum_number_of_problems: 100,
             current_number_of_problems: 0,
             total_diagnostics: Vec::new(),
+            overrides: HashMap::new(),
+            suppressions: Vec::new(),
         }
     }
 }
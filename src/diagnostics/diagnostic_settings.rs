@@ -0,0 +1,170 @@
+use std::collections::BTreeMap;
+
+use serde::Deserialize;
+use tower_lsp::lsp_types::DiagnosticSeverity;
+
+use super::diagnostic_codes::DiagnosticsCode;
+
+/**
+ * The settings of a diagnostics run, analyses that are opt-in are disabled by default
+ */
+#[derive(Clone, Debug, Deserialize, Eq, PartialEq)]
+#[serde(rename_all = "camelCase", default)]
+pub struct DiagnosticSettings {
+    /**
+     * If diagnostics are disabled, no analysis runs at all
+     */
+    pub enable: bool,
+
+    /**
+     * Overrides for the level of single codes, either by their rule id like "UnsafeVariable" or their number like "2000"
+     */
+    pub codes: BTreeMap<String, DiagnosticLevel>,
+
+    /**
+     * Predicates that only occur in bodies while the same name is defined with another arity
+     */
+    pub arity_mismatch: DiagnosticLevel,
+
+    /**
+     * Predicates that are derived, but neither used in a body or condition nor shown
+     */
+    pub unused_predicate: DiagnosticLevel,
+
+    /**
+     * #show p/n. statements for predicates that do not occur in the program
+     */
+    pub unmatched_show: DiagnosticLevel,
+
+    /**
+     * Named variables that occur only once in a statement
+     */
+    pub singleton_variable: DiagnosticLevel,
+
+    /**
+     * Negated predicates that are part of a cycle in the dependency graph
+     */
+    pub non_stratified_negation: DiagnosticLevel,
+
+    /**
+     * Cycles in the dependency graph through an odd number of negations
+     */
+    pub odd_loop: DiagnosticLevel,
+}
+
+impl Default for DiagnosticSettings {
+    fn default() -> Self {
+        DiagnosticSettings {
+            enable: true,
+            codes: BTreeMap::new(),
+            arity_mismatch: DiagnosticLevel::Off,
+            unused_predicate: DiagnosticLevel::Hint,
+            unmatched_show: DiagnosticLevel::Warning,
+            singleton_variable: DiagnosticLevel::Warning,
+            non_stratified_negation: DiagnosticLevel::Information,
+            odd_loop: DiagnosticLevel::Information,
+        }
+    }
+}
+
+impl DiagnosticSettings {
+    /**
+     * Get the level a code is overridden with
+     */
+    pub fn get_override(&self, code: DiagnosticsCode) -> Option<DiagnosticLevel> {
+        self.codes
+            .get(code.get_rule_id())
+            .or_else(|| self.codes.get(&code.into_i32().to_string()))
+            .copied()
+    }
+
+    /**
+     * Get the severity of an analysis, an override of its code takes precedence over the level of the rule
+     */
+    pub fn get_severity(
+        &self,
+        code: DiagnosticsCode,
+        level: DiagnosticLevel,
+    ) -> Option<DiagnosticSeverity> {
+        self.get_override(code).unwrap_or(level).get_severity()
+    }
+
+    /**
+     * Check if diagnostics with a code are reported at all, codes without a rule are always analyzed
+     */
+    pub fn is_enabled(&self, code: DiagnosticsCode) -> bool {
+        let level = match code {
+            DiagnosticsCode::ArityMismatch => self.arity_mismatch,
+            DiagnosticsCode::UnusedPredicate => self.unused_predicate,
+            DiagnosticsCode::UnmatchedShow => self.unmatched_show,
+            DiagnosticsCode::SingletonVariable => self.singleton_variable,
+            DiagnosticsCode::NonStratifiedNegation => self.non_stratified_negation,
+            DiagnosticsCode::OddLoop => self.odd_loop,
+            _ => DiagnosticLevel::Warning,
+        };
+
+        self.enable && self.get_severity(code, level).is_some()
+    }
+}
+
+/**
+ * The severity a diagnostic is reported with, diagnostics that are off are not analyzed at all
+ */
+#[derive(Clone, Copy, Debug, Deserialize, Eq, PartialEq)]
+#[serde(rename_all = "lowercase")]
+pub enum DiagnosticLevel {
+    Off,
+    Error,
+    Warning,
+    Information,
+    Hint,
+}
+
+impl DiagnosticLevel {
+    pub fn get_severity(self) -> Option<DiagnosticSeverity> {
+        match self {
+            DiagnosticLevel::Off => None,
+            DiagnosticLevel::Error => Some(DiagnosticSeverity::ERROR),
+            DiagnosticLevel::Warning => Some(DiagnosticSeverity::WARNING),
+            DiagnosticLevel::Information => Some(DiagnosticSeverity::INFORMATION),
+            DiagnosticLevel::Hint => Some(DiagnosticSeverity::HINT),
+        }
+    }
+
+    /**
+     * Read a level from its name, e.g. from a command line argument
+     */
+    pub fn from_name(name: &str) -> Option<DiagnosticLevel> {
+        match name {
+            "off" => Some(DiagnosticLevel::Off),
+            "error" => Some(DiagnosticLevel::Error),
+            "warning" => Some(DiagnosticLevel::Warning),
+            "information" => Some(DiagnosticLevel::Information),
+            "hint" => Some(DiagnosticLevel::Hint),
+            _ => None,
+        }
+    }
+}
+
+#[test]
+fn codes_should_be_overridden_by_rule_id_or_number() {
+    let settings: DiagnosticSettings = serde_json::from_value(serde_json::json!({
+        "arityMismatch": "hint",
+        "codes": {"ArityMismatch": "error", "2000": "off"}
+    }))
+    .unwrap();
+
+    assert!(settings.enable);
+    assert_eq!(
+        settings.get_severity(DiagnosticsCode::ArityMismatch, settings.arity_mismatch),
+        Some(DiagnosticSeverity::ERROR)
+    );
+    assert_eq!(
+        settings.get_override(DiagnosticsCode::UnsafeVariable),
+        Some(DiagnosticLevel::Off)
+    );
+    assert_eq!(
+        settings.get_override(DiagnosticsCode::UnusedPredicate),
+        None
+    );
+}
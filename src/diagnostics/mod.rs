@@ -1,31 +1,106 @@
-use crate::diagnostics::statement_analysis::statement_analysis;
+use crate::diagnostics::dependency_analysis::negation_cycle_analysis;
+use crate::diagnostics::diagnostic_codes::DiagnosticsCode;
+use crate::diagnostics::predicate_analysis::{
+    arity_mismatch_analysis, undefined_predicate_analysis, unmatched_show_analysis,
+    unused_predicate_analysis,
+};
+use crate::diagnostics::statement_analysis::{singleton_variable_analysis, statement_analysis};
 use crate::document::DocumentData;
+use crate::workspace::program::Program;
 
-use self::{diagnostic_run_data::DiagnosticsRunData, tree_error_analysis::search_for_tree_error};
+use self::{
+    diagnostic_run_data::DiagnosticsRunData, diagnostic_settings::DiagnosticSettings,
+    suppression::get_suppressions, tree_error_analysis::search_for_tree_error,
+};
 
-mod diagnostic_codes;
+pub mod dependency_analysis;
+pub mod diagnostic_codes;
 mod diagnostic_run_data;
-mod statement_analysis;
+pub mod diagnostic_settings;
+pub mod predicate_analysis;
+pub mod statement_analysis;
+pub mod suppression;
 mod tree_error_analysis;
 pub mod tree_utils;
 
 /**
- * Run the selected diagnostics on the parse tree
+ * Run the selected diagnostics on the parse tree, the program contains the included files of the document
  */
 pub fn run_diagnostics(
     document: DocumentData,
+    program: &Program,
+    settings: &DiagnosticSettings,
     maximum_number_of_problems: u32,
 ) -> Vec<tower_lsp::lsp_types::Diagnostic> {
+    if !settings.enable {
+        return Vec::new();
+    }
+
     //Setup the diagnostics run data object to be used for this diagnostics run
     let mut diagnostic_data = DiagnosticsRunData {
         maximum_number_of_problems,
         current_number_of_problems: 0,
         total_diagnostics: Vec::new(),
+        overrides: DiagnosticsCode::ALL
+            .into_iter()
+            .filter_map(|code| Some((code.into_i32(), settings.get_override(code)?)))
+            .collect(),
+        suppressions: get_suppressions(&document),
     };
 
     search_for_tree_error(&mut diagnostic_data, &document);
 
     statement_analysis(&mut diagnostic_data, &document);
 
+    if let Some(severity) = settings.get_severity(
+        DiagnosticsCode::SingletonVariable,
+        settings.singleton_variable,
+    ) {
+        singleton_variable_analysis(&mut diagnostic_data, &document, severity);
+    }
+
+    let arity_mismatch_severity =
+        settings.get_severity(DiagnosticsCode::ArityMismatch, settings.arity_mismatch);
+
+    undefined_predicate_analysis(
+        &mut diagnostic_data,
+        &document,
+        program,
+        arity_mismatch_severity.is_some(),
+    );
+
+    if let Some(severity) = arity_mismatch_severity {
+        arity_mismatch_analysis(&mut diagnostic_data, &document, program, severity);
+    }
+
+    if let Some(severity) =
+        settings.get_severity(DiagnosticsCode::UnusedPredicate, settings.unused_predicate)
+    {
+        unused_predicate_analysis(&mut diagnostic_data, &document, program, severity);
+    }
+
+    if let Some(severity) =
+        settings.get_severity(DiagnosticsCode::UnmatchedShow, settings.unmatched_show)
+    {
+        unmatched_show_analysis(&mut diagnostic_data, &document, program, severity);
+    }
+
+    let non_stratified_severity = settings.get_severity(
+        DiagnosticsCode::NonStratifiedNegation,
+        settings.non_stratified_negation,
+    );
+    let odd_loop_severity = settings.get_severity(DiagnosticsCode::OddLoop, settings.odd_loop);
+    if non_stratified_severity.is_some() || odd_loop_severity.is_some() {
+        negation_cycle_analysis(
+            &mut diagnostic_data,
+            &document,
+            program,
+            non_stratified_severity,
+            odd_loop_severity,
+        );
+    }
+
+    diagnostic_data.report_suppressions(|code| settings.is_enabled(code));
+
     diagnostic_data.total_diagnostics
 }
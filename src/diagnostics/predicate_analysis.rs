@@ -0,0 +1,681 @@
+use std::collections::HashSet;
+
+use tower_lsp::lsp_types::{DiagnosticRelatedInformation, DiagnosticSeverity, Location};
+use tree_sitter::Node;
+
+use crate::{
+    document::DocumentData,
+    rename::predicate::get_identifier_range,
+    semantics::predicate_occurence_semantics::{
+        PredicateOccurenceLocation, PredicateOccurenceSemantics, SignatureSemantics,
+    },
+    utils::range::to_lsp_range,
+    workspace::program::Program,
+};
+
+#[cfg(test)]
+use crate::test_utils::create_test_document;
+#[cfg(test)]
+use std::str::FromStr;
+#[cfg(test)]
+use tower_lsp::lsp_types::Url;
+
+use super::{diagnostic_codes::DiagnosticsCode, diagnostic_run_data::DiagnosticsRunData};
+
+/**
+ * Warn about predicates of the document that are used, but never defined anywhere in the program.
+ * If arity mismatches are reported, predicates whose name is defined with another arity are left to that analysis
+ */
+pub fn undefined_predicate_analysis(
+    diagnostic_data: &mut DiagnosticsRunData,
+    document: &DocumentData,
+    program: &Program,
+    report_arity_mismatches: bool,
+) {
+    let defined = get_defined_predicates(program);
+
+    for refmulti in document.semantics.predicate_semantics.predicates.iter() {
+        let (identifier, arity) = refmulti.key();
+        if defined.contains(refmulti.key()) {
+            continue;
+        }
+        if report_arity_mismatches && !get_other_arities(identifier, &defined).is_empty() {
+            continue;
+        }
+
+        let mut occurences: Vec<&PredicateOccurenceSemantics> = refmulti.value().iter().collect();
+        occurences.sort_by_key(|occurence| occurence.range.start_byte);
+
+        for occurence in occurences {
+            //If we reached the error limit stop analyzing further
+            if diagnostic_data.current_number_of_problems
+                >= diagnostic_data.maximum_number_of_problems
+            {
+                return;
+            }
+
+            // Function symbols and constants are no predicates
+            let range = match get_identifier_range(document, occurence.range) {
+                Some(range) => range,
+                None => continue,
+            };
+
+            let message = match get_closest_predicate(identifier, *arity, &defined) {
+                Some((closest_identifier, closest_arity)) => format!(
+                    "'{}/{}' is never defined, did you mean '{}/{}'?",
+                    identifier, arity, closest_identifier, closest_arity
+                ),
+                None => format!("'{}/{}' is never defined", identifier, arity),
+            };
+
+            diagnostic_data.create_linter_diagnostic(
+                range,
+                DiagnosticSeverity::WARNING,
+                DiagnosticsCode::UndefinedPredicate.into_i32(),
+                message,
+            );
+        }
+    }
+}
+
+/**
+ * Warn about predicates that only occur in bodies while the same name is defined with another arity,
+ * these are usually a typo like edge(X,Y,W) instead of edge(X,Y)
+ */
+pub fn arity_mismatch_analysis(
+    diagnostic_data: &mut DiagnosticsRunData,
+    document: &DocumentData,
+    program: &Program,
+    severity: DiagnosticSeverity,
+) {
+    let defined = get_defined_predicates(program);
+
+    for refmulti in document.semantics.predicate_semantics.predicates.iter() {
+        let (identifier, arity) = refmulti.key();
+        if defined.contains(refmulti.key()) {
+            continue;
+        }
+
+        let other_arities = get_other_arities(identifier, &defined);
+        if other_arities.is_empty() {
+            continue;
+        }
+
+        let message = format!(
+            "'{}/{}' only occurs in bodies, but '{}' is defined with arity {}",
+            identifier,
+            arity,
+            identifier,
+            other_arities
+                .iter()
+                .map(|arity| arity.to_string())
+                .collect::<Vec<String>>()
+                .join(", ")
+        );
+
+        let related_information: Vec<DiagnosticRelatedInformation> = other_arities
+            .iter()
+            .filter_map(|other_arity| {
+                let definition = get_first_definition(program, identifier, *other_arity)?;
+                Some(DiagnosticRelatedInformation {
+                    location: Location::new(definition.uri, to_lsp_range(definition.range)),
+                    message: format!("'{}/{}' is defined here", identifier, other_arity),
+                })
+            })
+            .collect();
+
+        let mut occurences: Vec<&PredicateOccurenceSemantics> = refmulti.value().iter().collect();
+        occurences.sort_by_key(|occurence| occurence.range.start_byte);
+
+        for occurence in occurences {
+            //If we reached the error limit stop analyzing further
+            if diagnostic_data.current_number_of_problems
+                >= diagnostic_data.maximum_number_of_problems
+            {
+                return;
+            }
+
+            // Function symbols and constants are no predicates
+            let range = match get_identifier_range(document, occurence.range) {
+                Some(range) => range,
+                None => continue,
+            };
+
+            diagnostic_data.create_linter_diagnostic_with_related_information(
+                range,
+                severity,
+                DiagnosticsCode::ArityMismatch.into_i32(),
+                message.clone(),
+                related_information.clone(),
+            );
+        }
+    }
+}
+
+/**
+ * Report predicates that are derived in the document, but never used in a body or condition and never shown.
+ * Without any #show statement every atom is part of the output, so nothing is reported in that case
+ */
+pub fn unused_predicate_analysis(
+    diagnostic_data: &mut DiagnosticsRunData,
+    document: &DocumentData,
+    program: &Program,
+    severity: DiagnosticSeverity,
+) {
+    if !has_show_statement(program) {
+        return;
+    }
+
+    for refmulti in document.semantics.predicate_semantics.predicates.iter() {
+        let (identifier, arity) = refmulti.key();
+        if program
+            .predicate_semantics
+            .show_signatures
+            .contains_key(refmulti.key())
+        {
+            continue;
+        }
+
+        let occurences = match program.predicate_semantics.predicates.get(refmulti.key()) {
+            Some(occurences) => occurences,
+            None => continue,
+        };
+
+        // Occurences in heads that do not define the predicate, like in #heuristic, use it as well
+        let is_used = occurences.iter().any(|occurence| {
+            occurence.location != PredicateOccurenceLocation::Head
+                || program
+                    .get_document(&occurence.uri)
+                    .is_some_and(|document| !is_definition(document, occurence))
+        });
+        if is_used {
+            continue;
+        }
+
+        let mut definitions: Vec<&PredicateOccurenceSemantics> = refmulti
+            .value()
+            .iter()
+            .filter(|occurence| is_definition(document, occurence))
+            .collect();
+        definitions.sort_by_key(|occurence| occurence.range.start_byte);
+
+        for definition in definitions {
+            //If we reached the error limit stop analyzing further
+            if diagnostic_data.current_number_of_problems
+                >= diagnostic_data.maximum_number_of_problems
+            {
+                return;
+            }
+
+            let range = match get_identifier_range(document, definition.range) {
+                Some(range) => range,
+                None => continue,
+            };
+
+            diagnostic_data.create_linter_diagnostic(
+                range,
+                severity,
+                DiagnosticsCode::UnusedPredicate.into_i32(),
+                format!(
+                    "'{}/{}' is derived, but never used or shown",
+                    identifier, arity
+                ),
+            );
+        }
+    }
+}
+
+/**
+ * Report #show p/n. statements of the document for predicates that do not occur anywhere in the program
+ */
+pub fn unmatched_show_analysis(
+    diagnostic_data: &mut DiagnosticsRunData,
+    document: &DocumentData,
+    program: &Program,
+    severity: DiagnosticSeverity,
+) {
+    let mut signatures: Vec<((String, usize), SignatureSemantics)> = document
+        .semantics
+        .predicate_semantics
+        .show_signatures
+        .iter()
+        .filter(|refmulti| !occurs_as_predicate(program, refmulti.key()))
+        .flat_map(|refmulti| {
+            refmulti
+                .value()
+                .iter()
+                .map(|signature| (refmulti.key().clone(), signature.clone()))
+                .collect::<Vec<_>>()
+        })
+        .collect();
+    signatures.sort_by_key(|(_, signature)| signature.range.start_byte);
+
+    if signatures.is_empty() {
+        return;
+    }
+    let defined = get_defined_predicates(program);
+
+    for ((identifier, arity), signature) in signatures {
+        //If we reached the error limit stop analyzing further
+        if diagnostic_data.current_number_of_problems >= diagnostic_data.maximum_number_of_problems
+        {
+            return;
+        }
+
+        let message = match get_closest_predicate(&identifier, arity, &defined) {
+            Some((closest_identifier, closest_arity)) => format!(
+                "'{}/{}' is shown, but never occurs in the program, did you mean '{}/{}'?",
+                identifier, arity, closest_identifier, closest_arity
+            ),
+            None => format!(
+                "'{}/{}' is shown, but never occurs in the program",
+                identifier, arity
+            ),
+        };
+
+        diagnostic_data.create_linter_diagnostic(
+            signature.range,
+            severity,
+            DiagnosticsCode::UnmatchedShow.into_i32(),
+            message,
+        );
+    }
+}
+
+/**
+ * Check if a predicate occurs anywhere in the program, function symbols with the same name and arity do not count
+ */
+fn occurs_as_predicate(program: &Program, predicate: &(String, usize)) -> bool {
+    program
+        .predicate_semantics
+        .predicates
+        .get(predicate)
+        .is_some_and(|occurences| {
+            occurences.iter().any(|occurence| {
+                program.get_document(&occurence.uri).is_some_and(|document| {
+                    get_identifier_range(document, occurence.range).is_some()
+                })
+            })
+        })
+}
+
+/**
+ * Check if any document of the program restricts the output with a #show statement
+ */
+fn has_show_statement(program: &Program) -> bool {
+    program.documents.iter().any(|document| {
+        let root = document.tree.root_node();
+        let has_show = root
+            .children(&mut root.walk())
+            .any(|statement| statement.child(0).map(|child| child.kind()) == Some("SHOW"));
+        has_show
+    })
+}
+
+/**
+ * Get the sorted arities a name is defined with, the predicate itself is not defined if it is used with another arity
+ */
+fn get_other_arities(identifier: &str, defined: &HashSet<(String, usize)>) -> Vec<usize> {
+    let mut arities: Vec<usize> = defined
+        .iter()
+        .filter(|(defined_identifier, _)| defined_identifier == identifier)
+        .map(|(_, defined_arity)| *defined_arity)
+        .collect();
+    arities.sort();
+    arities
+}
+
+/**
+ * Find the first occurence that defines a predicate, predicates that are only declared with #defined have none
+ */
+fn get_first_definition(
+    program: &Program,
+    identifier: &str,
+    arity: usize,
+) -> Option<PredicateOccurenceSemantics> {
+    let occurences = program
+        .predicate_semantics
+        .predicates
+        .get(&(identifier.to_string(), arity))?;
+
+    occurences
+        .iter()
+        .filter(|occurence| {
+            occurence.location == PredicateOccurenceLocation::Head
+                && program
+                    .get_document(&occurence.uri)
+                    .is_some_and(|document| is_definition(document, occurence))
+        })
+        .min_by_key(|occurence| (occurence.uri.to_string(), occurence.range.start_byte))
+        .cloned()
+}
+
+/**
+ * Find all predicates that can be derived in the program. These are predicates in a head, a fact, a choice or an #external statement.
+ * Predicates declared with #defined are treated as defined as well
+ */
+pub fn get_defined_predicates(program: &Program) -> HashSet<(String, usize)> {
+    let mut defined = HashSet::new();
+
+    for refmulti in program.predicate_semantics.predicates.iter() {
+        let is_defined = refmulti.value().iter().any(|occurence| {
+            occurence.location == PredicateOccurenceLocation::Head
+                && program
+                    .get_document(&occurence.uri)
+                    .is_some_and(|document| is_definition(document, occurence))
+        });
+
+        if is_defined {
+            defined.insert(refmulti.key().clone());
+        }
+    }
+
+    for document in &program.documents {
+        defined.extend(get_declared_predicates(document));
+    }
+
+    defined
+}
+
+/**
+ * Check if an occurence in the head of a statement defines the predicate.
+ * Atoms in directives like #show or #heuristic are outside of a body as well, but do not define anything
+ */
+pub fn is_definition(document: &DocumentData, occurence: &PredicateOccurenceSemantics) -> bool {
+    if get_identifier_range(document, occurence.range).is_none() {
+        return false;
+    }
+
+    let mut node = document
+        .tree
+        .root_node()
+        .descendant_for_byte_range(occurence.range.start_byte, occurence.range.end_byte);
+
+    while let Some(current) = node {
+        if current.kind() == "statement" {
+            return current
+                .child(0)
+                .is_some_and(|child| child.kind() == "head" || child.kind() == "EXTERNAL");
+        }
+        node = current.parent();
+    }
+
+    false
+}
+
+/**
+ * Find the predicates declared with #defined p/n in a document
+ */
+fn get_declared_predicates(document: &DocumentData) -> Vec<(String, usize)> {
+    let root = document.tree.root_node();
+
+    root.children(&mut root.walk())
+        .filter(|statement| statement.child(0).map(|child| child.kind()) == Some("DEFINED"))
+        .filter_map(|statement| get_signature(document, &statement))
+        .collect()
+}
+
+/**
+ * Read the signature of a statement in the form of #directive identifier/arity.
+ */
+fn get_signature(document: &DocumentData, statement: &Node) -> Option<(String, usize)> {
+    let identifier = statement
+        .child(1)
+        .filter(|node| node.kind() == "identifier")?;
+    let arity = statement.child(3).filter(|node| node.kind() == "NUMBER")?;
+
+    Some((
+        document.get_source_for_range(identifier.range()),
+        document.get_source_for_range(arity.range()).parse().ok()?,
+    ))
+}
+
+/**
+ * Find the defined predicate that is most similar to a predicate, typos in the name count as much as a different arity.
+ * Predicates that are too different are not suggested
+ */
+fn get_closest_predicate(
+    identifier: &str,
+    arity: usize,
+    defined: &HashSet<(String, usize)>,
+) -> Option<(String, usize)> {
+    let maximum_distance = identifier.chars().count() / 3;
+
+    defined
+        .iter()
+        .map(|(defined_identifier, defined_arity)| {
+            let distance =
+                get_edit_distance(identifier, defined_identifier) + arity.abs_diff(*defined_arity);
+            (distance, defined_identifier, defined_arity)
+        })
+        .filter(|(distance, _, _)| *distance <= maximum_distance)
+        .min()
+        .map(|(_, identifier, arity)| (identifier.clone(), *arity))
+}
+
+/**
+ * The number of characters that have to be inserted, removed or replaced to turn one word into the other
+ */
+fn get_edit_distance(a: &str, b: &str) -> usize {
+    let b: Vec<char> = b.chars().collect();
+    let mut previous: Vec<usize> = (0..=b.len()).collect();
+
+    for (i, character_a) in a.chars().enumerate() {
+        let mut current = vec![i + 1; b.len() + 1];
+
+        for (j, character_b) in b.iter().enumerate() {
+            let substitution = previous[j] + usize::from(character_a != *character_b);
+            current[j + 1] = substitution.min(previous[j + 1] + 1).min(current[j] + 1);
+        }
+
+        previous = current;
+    }
+
+    previous[b.len()]
+}
+
+#[cfg(test)]
+fn get_test_messages(document: &DocumentData, program: &Program) -> Vec<String> {
+    let mut diags = DiagnosticsRunData::create_test_diagnostics();
+    undefined_predicate_analysis(&mut diags, document, program, false);
+
+    let mut messages: Vec<String> = diags
+        .total_diagnostics
+        .iter()
+        .map(|diagnostic| diagnostic.message.clone())
+        .collect();
+    messages.sort();
+    messages
+}
+
+#[test]
+fn undefined_predicates_should_be_detected_with_a_suggestion() {
+    let document = create_test_document(
+        "schedule(1,2).\n{ assign(T) : task(T) }.\na :- scheudle(1,2), assign(f(1)), not b."
+            .to_string(),
+    );
+    let program = Program::new(vec![document.clone()]);
+
+    assert_eq!(
+        get_test_messages(&document, &program),
+        vec![
+            "'b/0' is never defined",
+            "'scheudle/2' is never defined, did you mean 'schedule/2'?",
+            "'task/1' is never defined",
+        ]
+    );
+}
+
+#[test]
+fn predicates_defined_in_other_files_or_declared_should_not_be_undefined() {
+    let document = create_test_document(
+        "#defined b/0.\n#external e.\n#show c/1.\na :- b, c(1), e, d(1).".to_string(),
+    );
+    let instance = DocumentData::from_source(
+        Url::from_str("file:///instance.lp").unwrap(),
+        "c(1). d(X) :- c(X).",
+        1,
+    );
+    let program = Program::new(vec![document.clone(), instance]);
+
+    assert!(get_test_messages(&document, &program).is_empty());
+}
+
+#[test]
+fn edit_distance_should_count_changed_characters() {
+    assert_eq!(get_edit_distance("schedule", "scheudle"), 2);
+    assert_eq!(get_edit_distance("task", "tasks"), 1);
+    assert_eq!(get_edit_distance("", "abc"), 3);
+}
+
+#[test]
+fn arity_mismatch_should_point_to_the_other_definitions() {
+    let document = create_test_document("reach(X) :- edge(X,Y,W), reach(Y).".to_string());
+    let instance = DocumentData::from_source(
+        Url::from_str("file:///instance.lp").unwrap(),
+        "edge(1,2). reach(1).",
+        1,
+    );
+    let program = Program::new(vec![document.clone(), instance]);
+
+    let mut diags = DiagnosticsRunData::create_test_diagnostics();
+    arity_mismatch_analysis(&mut diags, &document, &program, DiagnosticSeverity::WARNING);
+
+    assert_eq!(diags.total_diagnostics.len(), 1);
+    let diagnostic = &diags.total_diagnostics[0];
+    assert_eq!(
+        diagnostic.message,
+        "'edge/3' only occurs in bodies, but 'edge' is defined with arity 2"
+    );
+    assert_eq!(diagnostic.range.start.character, 12);
+
+    let related_information = diagnostic.related_information.as_ref().unwrap();
+    assert_eq!(related_information.len(), 1);
+    assert_eq!(
+        related_information[0].location.uri.as_str(),
+        "file:///instance.lp"
+    );
+    assert_eq!(related_information[0].location.range.start.character, 0);
+}
+
+#[test]
+fn arity_mismatches_should_not_be_reported_as_undefined_as_well() {
+    use super::{
+        diagnostic_settings::{DiagnosticLevel, DiagnosticSettings},
+        run_diagnostics,
+    };
+    use tower_lsp::lsp_types::NumberOrString;
+
+    let document =
+        create_test_document("edge(1,2).\nreach(X) :- edge(X,Y,W), reach(Y).".to_string());
+    let program = Program::new(vec![document.clone()]);
+
+    let get_codes = |arity_mismatch| {
+        run_diagnostics(
+            document.clone(),
+            &program,
+            &DiagnosticSettings {
+                arity_mismatch,
+                singleton_variable: DiagnosticLevel::Off,
+                ..Default::default()
+            },
+            100,
+        )
+        .into_iter()
+        .filter(|diagnostic| diagnostic.range.start.line == 1)
+        .map(|diagnostic| diagnostic.code)
+        .collect::<Vec<_>>()
+    };
+
+    assert_eq!(
+        get_codes(DiagnosticLevel::Warning),
+        vec![Some(NumberOrString::Number(DiagnosticsCode::ArityMismatch.into_i32()))]
+    );
+    assert_eq!(
+        get_codes(DiagnosticLevel::Off),
+        vec![Some(NumberOrString::Number(DiagnosticsCode::UndefinedPredicate.into_i32()))]
+    );
+}
+
+#[test]
+fn unused_predicates_should_only_be_reported_with_show_statements() {
+    let get_messages = |source: &str| {
+        let document = create_test_document(source.to_string());
+        let program = Program::new(vec![document.clone()]);
+
+        let mut diags = DiagnosticsRunData::create_test_diagnostics();
+        unused_predicate_analysis(&mut diags, &document, &program, DiagnosticSeverity::HINT);
+        diags
+            .total_diagnostics
+            .iter()
+            .map(|diagnostic| diagnostic.message.clone())
+            .collect::<Vec<String>>()
+    };
+
+    let source = "a(1). helper(X) :- a(X). b(X) :- a(X). c(X) :- b(X). #heuristic a(1). [1,level]";
+    assert!(get_messages(source).is_empty());
+    assert_eq!(
+        get_messages(&format!("{}\n#show c/1.", source)),
+        vec!["'helper/1' is derived, but never used or shown"]
+    );
+}
+
+#[test]
+fn predicates_used_in_sibling_files_should_not_be_unused() {
+    use crate::{test_utils::TestFolder, workspace::Workspace};
+    use dashmap::DashMap;
+
+    let folder = TestFolder::new("unused_siblings");
+    let directory = &folder.path;
+    std::fs::write(
+        directory.join("main.lp"),
+        "#include \"defs.lp\". #include \"rules.lp\".",
+    )
+    .unwrap();
+    std::fs::write(directory.join("defs.lp"), "edge(1,2). node(1).\n#show reach/1.").unwrap();
+    std::fs::write(directory.join("rules.lp"), "reach(X) :- edge(X,_).").unwrap();
+
+    let workspace = Workspace::new();
+    workspace.index_folders(vec![directory.clone()]);
+    let uri = Url::from_file_path(directory.join("defs.lp")).unwrap();
+    let document = workspace.get_document(&uri, &DashMap::new()).unwrap();
+    let program = workspace.get_root_program(&uri, &DashMap::new());
+
+    let mut diags = DiagnosticsRunData::create_test_diagnostics();
+    unused_predicate_analysis(&mut diags, &document, &program, DiagnosticSeverity::HINT);
+
+    let messages: Vec<String> = diags
+        .total_diagnostics
+        .iter()
+        .map(|diagnostic| diagnostic.message.clone())
+        .collect();
+    assert_eq!(messages, vec!["'node/1' is derived, but never used or shown"]);
+}
+
+#[test]
+fn show_signatures_without_predicates_should_be_reported() {
+    let document = create_test_document(
+        "colour(1). node(f(1)).\n#show color/1.\n#show colour/1.\n#show f/1.".to_string(),
+    );
+    let program = Program::new(vec![document.clone()]);
+
+    let mut diags = DiagnosticsRunData::create_test_diagnostics();
+    unmatched_show_analysis(&mut diags, &document, &program, DiagnosticSeverity::WARNING);
+
+    // f only occurs as a function symbol, so nothing is shown for it
+    let messages: Vec<(u32, &str)> = diags
+        .total_diagnostics
+        .iter()
+        .map(|diagnostic| (diagnostic.range.start.line, diagnostic.message.as_str()))
+        .collect();
+    assert_eq!(
+        messages,
+        vec![
+            (
+                1,
+                "'color/1' is shown, but never occurs in the program, did you mean 'colour/1'?"
+            ),
+            (3, "'f/1' is shown, but never occurs in the program"),
+        ]
+    );
+}
@@ -1,4 +1,4 @@
-use std::collections::HashSet;
+use std::collections::{HashMap, HashSet};
 use tower_lsp::lsp_types::DiagnosticSeverity;
 use tree_sitter::{Node, Query, QueryCursor};
 
@@ -44,6 +44,42 @@ pub fn statement_analysis(diagnostic_data: &mut DiagnosticsRunData, document: &D
     }
 }
 
+/**
+ * Walk through the parse tree and warn about named variables that occur only once in a statement
+ */
+pub fn singleton_variable_analysis(
+    diagnostic_data: &mut DiagnosticsRunData,
+    document: &DocumentData,
+    severity: DiagnosticSeverity,
+) {
+    let mut cursor = document.tree.walk();
+
+    let mut reached_root = false;
+    while !reached_root {
+        let node = cursor.node();
+
+        //If we reached the error limit stop analyzing further
+        if diagnostic_data.current_number_of_problems >= diagnostic_data.maximum_number_of_problems
+        {
+            return;
+        };
+
+        if node.kind() == "statement" {
+            check_singleton_variables_of_statement(&node, document, diagnostic_data, severity);
+        }
+
+        if cursor.goto_first_child() {
+            continue;
+        }
+
+        if cursor.goto_next_sibling() {
+            continue;
+        }
+
+        (cursor, reached_root) = retrace(cursor);
+    }
+}
+
 /**
  * Calculates the safe set for a set of dependencies
  */
@@ -132,7 +168,7 @@ fn get_dependencies_only_occuring_in_set(
 /**
  * Find all variables occuring in a part of the encoding
  */
-fn get_variables_in_statement<'a>(
+pub fn get_variables_in_statement<'a>(
     node: &tree_sitter::Node<'a>,
     source: &'a [u8],
 ) -> std::vec::Vec<(tree_sitter::Range, &'a str, tree_sitter::Node<'a>)> {
@@ -155,13 +191,9 @@ fn get_variables_in_statement<'a>(
 }
 
 /**
- * Check if a statement is safe
+ * Find the names of all variables in a statement that are unsafe, either globally or in a local context
  */
-fn check_safety_of_statement(
-    node: &Node,
-    document: &DocumentData,
-    diagnostics: &mut DiagnosticsRunData,
-) {
+pub fn get_unsafe_variables(node: &Node, document: &DocumentData) -> HashSet<String> {
     let statement_semantics = document
         .semantics
         .get_statement_semantics_for_node(node.id());
@@ -201,16 +233,30 @@ fn check_safety_of_statement(
         .cloned()
         .collect();
 
-    //Due to the fact that the variable locations could have changed in terms of byte range, we look for the variables again
-    let source = document.get_bytes();
-    let variable_locations = get_variables_in_statement(node, &source);
     let mut unsafe_vars = unsafe_set.clone();
 
-    //First combine the lists of all unsafe variables in the statements
+    //Combine the lists of all unsafe variables in the statements
     for (_, set) in local_unsafe_sets {
         unsafe_vars = unsafe_vars.union(&set).cloned().collect();
     }
 
+    unsafe_vars
+}
+
+/**
+ * Check if a statement is safe
+ */
+fn check_safety_of_statement(
+    node: &Node,
+    document: &DocumentData,
+    diagnostics: &mut DiagnosticsRunData,
+) {
+    let unsafe_vars = get_unsafe_variables(node, document);
+
+    //Due to the fact that the variable locations could have changed in terms of byte range, we look for the variables again
+    let source = document.get_bytes();
+    let variable_locations = get_variables_in_statement(node, &source);
+
     //Next we create a diagnostic for every variable we find in the variable_locations list that occurs in the unsafe_vars list
     for (location, var, _) in variable_locations {
         if unsafe_vars.contains(var) {
@@ -224,6 +270,56 @@ fn check_safety_of_statement(
     }
 }
 
+/**
+ * Check if a statement contains variables that occur only once, these should be written as _.
+ * Variables starting with _ are meant to be singletons and unsafe variables are already reported as errors
+ */
+fn check_singleton_variables_of_statement(
+    node: &Node,
+    document: &DocumentData,
+    diagnostics: &mut DiagnosticsRunData,
+    severity: DiagnosticSeverity,
+) {
+    let source = document.get_bytes();
+    let variable_locations = get_variables_in_statement(node, &source);
+
+    let mut occurences: HashMap<&str, usize> = HashMap::new();
+    for (_, var, _) in &variable_locations {
+        *occurences.entry(var).or_default() += 1;
+    }
+
+    let unsafe_vars = get_unsafe_variables(node, document);
+
+    for (location, var, _) in variable_locations {
+        if occurences[var] == 1 && !var.starts_with('_') && !unsafe_vars.contains(var) {
+            diagnostics.create_linter_diagnostic(
+                location,
+                severity,
+                DiagnosticsCode::SingletonVariable.into_i32(),
+                format!("'{}' occurs only once, use '_' instead", var),
+            )
+        }
+    }
+}
+
+#[test]
+fn singleton_variables_should_be_detected() {
+    let mut diags = DiagnosticsRunData::create_test_diagnostics();
+
+    singleton_variable_analysis(
+        &mut diags,
+        &create_test_document("a(X) :- b(X, Y), c(_Z). d(X) :- e(X, _).\nf(Y) :- g.".to_string()),
+        DiagnosticSeverity::WARNING,
+    );
+
+    assert_eq!(diags.total_diagnostics.len(), 1);
+    assert_eq!(
+        diags.total_diagnostics[0].message,
+        "'Y' occurs only once, use '_' instead"
+    );
+    assert_eq!(diags.total_diagnostics[0].range.start.character, 13);
+}
+
 #[test]
 fn no_variables_should_be_detected_as_safe() {
     let mut diags = DiagnosticsRunData::create_test_diagnostics();
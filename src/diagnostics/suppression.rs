@@ -0,0 +1,233 @@
+use tree_sitter::{Query, QueryCursor, Range};
+
+use crate::document::DocumentData;
+
+use super::diagnostic_codes::DiagnosticsCode;
+
+#[cfg(test)]
+use crate::test_utils::create_test_document;
+
+const DISABLE_NEXT_LINE: &str = "asp-lsp-disable-next-line";
+const DISABLE: &str = "asp-lsp-disable";
+
+/**
+ * A rule that is disabled by a comment, either for the next line or for the whole file
+ */
+#[derive(Clone, Debug, PartialEq)]
+pub struct Suppression {
+    /**
+     * The name of the rule as written in the comment, e.g. unsafe-variable. It is empty if the comment names no rule
+     */
+    pub rule: String,
+
+    /**
+     * The code of the rule, unknown rules and rules that can not be suppressed have none
+     */
+    pub code: Option<DiagnosticsCode>,
+
+    /**
+     * The line whose diagnostics are suppressed, file level suppressions have none
+     */
+    pub line: Option<usize>,
+
+    /**
+     * Where the rule is written in the comment, or the directive if there is no rule
+     */
+    pub range: Range,
+
+    /**
+     * Set as soon as a diagnostic was suppressed
+     */
+    pub used: bool,
+}
+
+impl Suppression {
+    /**
+     * Check if this suppression silences a diagnostic with a code that starts on a line
+     */
+    pub fn matches(&self, code_number: i32, line: usize) -> bool {
+        self.code.is_some_and(|code| code.into_i32() == code_number)
+            && (self.line.is_none() || self.line == Some(line))
+    }
+
+    /**
+     * The directive of the comment this suppression was read from
+     */
+    pub fn get_directive(&self) -> &'static str {
+        match self.line {
+            Some(_) => DISABLE_NEXT_LINE,
+            None => DISABLE,
+        }
+    }
+}
+
+/**
+ * Find the suppression comments of a document, e.g. % asp-lsp-disable-next-line unsafe-variable, singleton-variable
+ */
+pub fn get_suppressions(document: &DocumentData) -> Vec<Suppression> {
+    let mut query_cursor = QueryCursor::new();
+    let query = Query::new(tree_sitter_clingo::language(), "(single_comment) @comment").unwrap();
+    let source = document.get_bytes();
+
+    let mut suppressions = Vec::new();
+    for each_match in query_cursor.matches(&query, document.tree.root_node(), source.as_slice()) {
+        for capture in each_match.captures.iter() {
+            let comment = document.get_source_for_range(capture.node.range());
+            let text = comment.trim_start_matches('%').trim_start();
+
+            let (line, rules) = if let Some(rules) = text.strip_prefix(DISABLE_NEXT_LINE) {
+                (Some(capture.node.start_position().row + 1), rules)
+            } else if let Some(rules) = text.strip_prefix(DISABLE) {
+                (None, rules)
+            } else {
+                continue;
+            };
+
+            // The directive has to be followed by whitespace, asp-lsp-disabled is no directive
+            if !rules.is_empty() && !rules.starts_with(char::is_whitespace) {
+                continue;
+            }
+
+            let offset = comment.len() - rules.len();
+            let rules = split_rules(rules);
+
+            // A directive without rules disables nothing, it is reported like an unknown rule
+            if rules.is_empty() {
+                let directive = match line {
+                    Some(_) => DISABLE_NEXT_LINE,
+                    None => DISABLE,
+                };
+                suppressions.push(Suppression {
+                    rule: String::new(),
+                    code: None,
+                    line,
+                    range: get_range(&capture.node, comment.len() - text.len(), directive.len()),
+                    used: false,
+                });
+            }
+
+            for (rule_offset, rule) in rules {
+                let range = get_range(&capture.node, offset + rule_offset, rule.len());
+
+                suppressions.push(Suppression {
+                    rule: rule.to_string(),
+                    code: DiagnosticsCode::ALL
+                        .into_iter()
+                        .find(|code| code.is_suppressible() && code.get_name() == rule),
+                    line,
+                    range,
+                    used: false,
+                });
+            }
+        }
+    }
+
+    suppressions
+}
+
+/**
+ * Get the range of a part of a comment, comments never span multiple lines
+ */
+fn get_range(comment: &tree_sitter::Node, start: usize, length: usize) -> Range {
+    let mut range = comment.range();
+    range.start_byte += start;
+    range.end_byte = range.start_byte + length;
+    range.start_point.column += start;
+    range.end_point = range.start_point;
+    range.end_point.column += length;
+    range
+}
+
+/**
+ * Split the rules of a suppression comment at commas and whitespace, together with their byte offset
+ */
+fn split_rules(rules: &str) -> Vec<(usize, &str)> {
+    let mut result = Vec::new();
+    let mut start = None;
+
+    for (index, character) in rules.char_indices() {
+        let is_separator = character == ',' || character.is_whitespace();
+        match (start, is_separator) {
+            (None, false) => start = Some(index),
+            (Some(rule_start), true) => {
+                result.push((rule_start, &rules[rule_start..index]));
+                start = None;
+            }
+            _ => {}
+        }
+    }
+
+    if let Some(rule_start) = start {
+        result.push((rule_start, &rules[rule_start..]));
+    }
+
+    result
+}
+
+#[test]
+fn suppressions_should_be_read_from_comments() {
+    let document = create_test_document(
+        "% asp-lsp-disable unsafe-variable\na(X) :- b. % asp-lsp-disable-next-line singleton-variable,unknown\n% asp-lsp-disabled odd-loop"
+            .to_string(),
+    );
+
+    let suppressions = get_suppressions(&document);
+
+    assert_eq!(suppressions.len(), 3);
+    assert_eq!(suppressions[0].rule, "unsafe-variable");
+    assert_eq!(suppressions[0].code, Some(DiagnosticsCode::UnsafeVariable));
+    assert_eq!(suppressions[0].line, None);
+    assert_eq!(suppressions[0].range.start_point.column, 18);
+    assert_eq!(suppressions[0].range.end_point.column, 33);
+
+    assert_eq!(
+        suppressions[1].code,
+        Some(DiagnosticsCode::SingletonVariable)
+    );
+    assert_eq!(suppressions[1].line, Some(2));
+    assert_eq!(suppressions[2].rule, "unknown");
+    assert_eq!(suppressions[2].code, None);
+    assert_eq!(suppressions[2].range.start_point.column, 58);
+}
+
+#[test]
+fn suppressed_diagnostics_should_not_be_reported() {
+    use super::{diagnostic_settings::DiagnosticSettings, run_diagnostics};
+    use crate::workspace::program::Program;
+
+    let document = create_test_document(
+        "b.\n% asp-lsp-disable-next-line unsafe-variable\na(X) :- b.\nc(Y) :- b.\n% asp-lsp-disable-next-line unsafe-variable, nothing\nd :- b.\n% asp-lsp-disable arity-mismatch\n% asp-lsp-disable-next-line\ne(Z) :- b."
+            .to_string(),
+    );
+    let diagnostics = run_diagnostics(
+        document.clone(),
+        &Program::new(vec![document]),
+        &DiagnosticSettings::default(),
+        100,
+    );
+
+    let messages: Vec<(u32, String)> = diagnostics
+        .into_iter()
+        .map(|diagnostic| (diagnostic.range.start.line, diagnostic.message))
+        .collect();
+    assert_eq!(
+        messages,
+        vec![
+            (3, "'Y' is unsafe".to_string()),
+            // A directive without rules suppresses nothing
+            (8, "'Z' is unsafe".to_string()),
+            (
+                4,
+                "'unsafe-variable' is disabled, but nothing is suppressed".to_string()
+            ),
+            (
+                4,
+                "'nothing' is not a rule that can be disabled".to_string()
+            ),
+            (
+                7,
+                "'asp-lsp-disable-next-line' needs the rules to disable, e.g. asp-lsp-disable-next-line unsafe-variable".to_string()
+            ),
+        ]
+    );
+}
@@ -3,8 +3,14 @@ use super::{
     tree_utils::humanize_token,
 };
 use crate::document::DocumentData;
+use serde_json::json;
 use tower_lsp::lsp_types::DiagnosticSeverity;
 
+/**
+ * Missing tokens that a quick fix can insert at the end of the diagnostic
+ */
+const INSERTABLE_TOKENS: [&str; 3] = [".", ")", "}"];
+
 /**
 * Search for errors in the parse tree.
 */
@@ -13,7 +19,7 @@ pub fn search_for_tree_error(diagnostic_data: &mut DiagnosticsRunData, document:
     for error in document.semantics.syntax.get_errors() {
         if error.prev_sibling_type == "statement" {
             //Found an error which is preceeded by an statement, most likely a . is missing
-            diagnostic_data.create_tree_sitter_diagnostic(
+            diagnostic_data.create_tree_sitter_diagnostic_with_data(
                 error.range,
                 DiagnosticSeverity::ERROR,
                 DiagnosticsCode::ExpectedDot.into_i32(),
@@ -25,6 +31,7 @@ pub fn search_for_tree_error(diagnostic_data: &mut DiagnosticsRunData, document:
                         .as_str()
                         .unwrap()
                 ),
+                json!({ "insert": "." }),
             );
 
             continue;
@@ -47,15 +54,25 @@ pub fn search_for_tree_error(diagnostic_data: &mut DiagnosticsRunData, document:
 
     for missing in document.semantics.syntax.get_missing() {
         //If node is missing, tell the user what we expected
-        diagnostic_data.create_tree_sitter_diagnostic(
-            missing.range,
-            DiagnosticSeverity::ERROR,
-            DiagnosticsCode::ExpectedMissingToken.into_i32(),
-            format!(
-                "syntax error while parsing, expected: '{}'",
-                humanize_token(&missing.missing)
-            ),
-        );
+        let token = humanize_token(&missing.missing);
+        let message = format!("syntax error while parsing, expected: '{}'", token);
+
+        if INSERTABLE_TOKENS.contains(&token) {
+            diagnostic_data.create_tree_sitter_diagnostic_with_data(
+                missing.range,
+                DiagnosticSeverity::ERROR,
+                DiagnosticsCode::ExpectedMissingToken.into_i32(),
+                message,
+                json!({ "insert": token }),
+            );
+        } else {
+            diagnostic_data.create_tree_sitter_diagnostic(
+                missing.range,
+                DiagnosticSeverity::ERROR,
+                DiagnosticsCode::ExpectedMissingToken.into_i32(),
+                message,
+            );
+        }
     }
 }
 
@@ -133,3 +150,24 @@ fn if_parser_misses_token_throw_missing_token() {
         )
     );
 }
+
+#[test]
+fn missing_tokens_should_carry_the_token_to_insert() {
+    let mut diags = DiagnosticsRunData::create_test_diagnostics();
+    let doc = create_test_document("a. d c :- a.\n{a(1).".to_string());
+
+    search_for_tree_error(&mut diags, &doc);
+
+    let data: Vec<Option<serde_json::Value>> = diags
+        .total_diagnostics
+        .into_iter()
+        .map(|diagnostic| diagnostic.data)
+        .collect();
+    assert_eq!(
+        data,
+        vec![
+            Some(json!({ "insert": "." })),
+            Some(json!({ "insert": "}" }))
+        ]
+    );
+}
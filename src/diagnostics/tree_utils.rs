@@ -7,8 +7,8 @@ pub fn humanize_token(token: &str) -> &str {
     match token {
         "RPAREN" => ")",
         "LPAREN" => "(",
-        "RBRACE" => "{",
-        "LBRACE" => "}",
+        "RBRACE" => "}",
+        "LBRACE" => "{",
         "COMMA" => ",",
         "DOT" => ".",
         _ => token,
@@ -29,6 +29,22 @@ impl DocumentData {
         }
     }
 
+    /**
+     * Parse the source of an encoding into a new document and generate the semantics for it
+     */
+    pub fn from_source(uri: Url, source: &str, version: i32) -> DocumentData {
+        let mut parser = Parser::new();
+        parser
+            .set_language(tree_sitter_clingo::language())
+            .expect("Error loading clingo grammar");
+
+        let tree = parser.parse(source, None).unwrap();
+
+        let mut document = DocumentData::new(uri, tree, Rope::from_str(source), version);
+        document.generate_semantics(None);
+        document
+    }
+
     pub fn get_bytes(&self) -> Vec<u8> {
         let mut array = Vec::with_capacity(self.source.len_bytes());
 
@@ -222,6 +222,50 @@ impl Documentation {
         Some(arguments)
     }
 
+    /**
+     * Find the predicate a doc comment is written for.
+     * Returns the identifier, the arity and the byte offset of the identifier inside the comment
+     */
+    pub fn get_documented_predicate(comment: &str) -> Option<(String, usize, usize)> {
+        if !comment.starts_with("%*") || !comment.ends_with("*%") || comment.len() <= 4 {
+            return None;
+        }
+
+        let content = &comment[2..comment.len() - 2];
+        let trimmed = content.trim_start();
+
+        if !trimmed.starts_with('#') {
+            return None;
+        }
+
+        let signature_start = 2 + content.len() - trimmed.len() + 1;
+        let signature_end = trimmed.find('.')?;
+        let signature = &trimmed[1..signature_end + 1];
+
+        let tree = {
+            let mut parser = Parser::new();
+            parser
+                .set_language(tree_sitter_clingo::language())
+                .expect("Error loading clingo grammar");
+            parser.parse(signature, None)?
+        };
+
+        let mut atom_node = tree.root_node();
+        while atom_node.kind() != "atom" {
+            atom_node = atom_node.child(0)?;
+        }
+
+        if Documentation::has_error(&atom_node) {
+            return None;
+        }
+
+        let identifier_node = atom_node.child(0)?;
+        let identifier = Documentation::get_atom_identifier(&atom_node, signature)?;
+        let arguments = Documentation::get_atom_arguments(&atom_node, signature)?;
+
+        Some((identifier, arguments.len(), signature_start + identifier_node.start_byte()))
+    }
+
     fn get_argument_descriptions(input: &str) -> DashMap<String, String> {
         input.lines()
             .map(str::trim_start)
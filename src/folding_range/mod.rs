@@ -0,0 +1,185 @@
+use tower_lsp::lsp_types::{FoldingRange, FoldingRangeKind, FoldingRangeParams};
+use tree_sitter::Node;
+
+use crate::document::DocumentData;
+
+#[cfg(test)]
+use crate::test_utils::create_test_document;
+
+/**
+ * Find the parts of a document that can be collapsed: statements, aggregates and block comments spanning multiple lines,
+ * runs of line comments and whole #program parts
+ */
+pub fn handle(document: &DocumentData, _params: &FoldingRangeParams) -> Option<Vec<FoldingRange>> {
+    let root = document.tree.root_node();
+
+    let mut ranges = Vec::new();
+    collect_ranges(root, &mut ranges);
+    collect_comment_blocks(&root, &mut ranges);
+    collect_program_parts(&root, &mut ranges);
+
+    // Clients only show one range per line, the outer range is kept
+    ranges.sort_by_key(|range| (range.start_line, std::cmp::Reverse(range.end_line)));
+    ranges.dedup_by_key(|range| range.start_line);
+
+    Some(ranges)
+}
+
+/**
+ * Walk through the tree and collect multi-line statements, aggregates and block comments
+ */
+fn collect_ranges(node: Node, ranges: &mut Vec<FoldingRange>) {
+    match node.kind() {
+        "multi_comment" => {
+            ranges.extend(create_range(
+                node.start_position().row,
+                node.end_position().row,
+                Some(FoldingRangeKind::Comment),
+            ));
+        }
+        "statement" => {
+            ranges.extend(create_range(
+                node.start_position().row,
+                node.end_position().row,
+                None,
+            ));
+        }
+        kind if kind.ends_with("aggregate") => {
+            // Only the elements between the braces are collapsed, e.g. not the bounds of #count{ ... } > 2
+            let braces: Vec<Node> = node
+                .children(&mut node.walk())
+                .filter(|child| matches!(child.kind(), "LBRACE" | "RBRACE"))
+                .collect();
+            if let [left, right] = braces.as_slice() {
+                ranges.extend(create_range(
+                    left.start_position().row,
+                    right.end_position().row,
+                    None,
+                ));
+            }
+        }
+        _ => {}
+    }
+
+    for child in node.children(&mut node.walk()) {
+        collect_ranges(child, ranges);
+    }
+}
+
+/**
+ * Line comments on consecutive lines are collapsed together
+ */
+fn collect_comment_blocks(root: &Node, ranges: &mut Vec<FoldingRange>) {
+    let mut block: Option<(usize, usize)> = None;
+
+    for child in root.children(&mut root.walk()) {
+        let row = child.start_position().row;
+
+        // A comment behind a statement does not start a block
+        let is_line_comment = child.kind() == "single_comment"
+            && child
+                .prev_sibling()
+                .map(|previous| previous.end_position().row < row)
+                .unwrap_or(true);
+
+        block = match block {
+            Some((start, end)) if is_line_comment && row == end + 1 => Some((start, row)),
+            Some((start, end)) => {
+                ranges.extend(create_range(start, end, Some(FoldingRangeKind::Comment)));
+                is_line_comment.then_some((row, row))
+            }
+            None => is_line_comment.then_some((row, row)),
+        };
+    }
+
+    if let Some((start, end)) = block {
+        ranges.extend(create_range(start, end, Some(FoldingRangeKind::Comment)));
+    }
+}
+
+/**
+ * A #program part reaches from its #program statement to the last statement or comment in front of the next one
+ */
+fn collect_program_parts(root: &Node, ranges: &mut Vec<FoldingRange>) {
+    let mut part: Option<(usize, usize)> = None;
+
+    for child in root.children(&mut root.walk()) {
+        let is_program = child.kind() == "statement"
+            && child.child(0).is_some_and(|block| block.kind() == "BLOCK");
+
+        if is_program {
+            if let Some((start, end)) = part {
+                ranges.extend(create_range(start, end, Some(FoldingRangeKind::Region)));
+            }
+            part = Some((child.start_position().row, child.end_position().row));
+        } else if let Some((_, end)) = part.as_mut() {
+            *end = child.end_position().row;
+        }
+    }
+
+    if let Some((start, end)) = part {
+        ranges.extend(create_range(start, end, Some(FoldingRangeKind::Region)));
+    }
+}
+
+fn create_range(
+    start_line: usize,
+    end_line: usize,
+    kind: Option<FoldingRangeKind>,
+) -> Option<FoldingRange> {
+    if end_line <= start_line {
+        return None;
+    }
+
+    Some(FoldingRange {
+        start_line: start_line as u32,
+        end_line: end_line as u32,
+        kind,
+        ..Default::default()
+    })
+}
+
+#[test]
+fn folding_ranges_should_be_found_in_the_tree() {
+    let document = create_test_document(
+        "%*! a/1
+ * Documented
+ *%
+a(X) :- b(X),
+    c(X).
+% first
+% second
+#program check.
+:- #count { X : a(X);
+            Y : b(Y) } > 2.
+d. % trailing
+#program base.
+e."
+        .to_string(),
+    );
+
+    let ranges: Vec<(u32, u32, Option<FoldingRangeKind>)> = handle(
+        &document,
+        &FoldingRangeParams {
+            text_document: tower_lsp::lsp_types::TextDocumentIdentifier::new(document.uri.clone()),
+            work_done_progress_params: Default::default(),
+            partial_result_params: Default::default(),
+        },
+    )
+    .unwrap()
+    .into_iter()
+    .map(|range| (range.start_line, range.end_line, range.kind))
+    .collect();
+
+    assert_eq!(
+        ranges,
+        vec![
+            (0, 2, Some(FoldingRangeKind::Comment)),
+            (3, 4, None),
+            (5, 6, Some(FoldingRangeKind::Comment)),
+            (7, 10, Some(FoldingRangeKind::Region)),
+            (8, 9, None),
+            (11, 12, Some(FoldingRangeKind::Region)),
+        ]
+    );
+}
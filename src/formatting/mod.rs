@@ -0,0 +1,669 @@
+use std::collections::HashMap;
+
+use tower_lsp::lsp_types::{
+    DocumentFormattingParams, DocumentRangeFormattingParams, FormattingOptions, Position, Range,
+    TextEdit,
+};
+use tree_sitter::Node;
+
+use crate::{document::DocumentData, utils::range::to_lsp_range};
+
+#[cfg(test)]
+use crate::test_utils::create_test_document;
+
+pub mod on_type;
+
+/**
+ * Statements that do not fit into a line of this length are broken into one body literal per line
+ */
+pub const MAX_LINE_LENGTH: usize = 80;
+
+/**
+ * A leaf of the parse tree together with the layout information the formatter needs
+ */
+struct Token {
+    text: String,
+
+    /**
+     * Whether the token is separated from the previous token of the statement by a space
+     */
+    space_before: bool,
+
+    /**
+     * Set for , and ; that separate the literals of a rule body
+     */
+    body_separator: bool,
+
+    /**
+     * The aggregate whose elements are separated by this ;
+     */
+    element_separator: Option<usize>,
+
+    /**
+     * The aggregate whose first element starts with this token
+     */
+    element_start: Option<usize>,
+}
+
+/**
+ * Format the whole document, nothing is changed if the document contains syntax errors
+ */
+pub fn handle(document: &DocumentData, params: &DocumentFormattingParams) -> Option<Vec<TextEdit>> {
+    let source = document.source.to_string();
+    let formatted = format_document(document, &params.options)?;
+    if formatted == source {
+        return Some(Vec::new());
+    }
+
+    let last_line = document.source.len_lines() - 1;
+    let end = Position::new(
+        last_line as u32,
+        document.source.line(last_line).len_chars() as u32,
+    );
+
+    Some(vec![TextEdit::new(
+        Range::new(Position::new(0, 0), end),
+        formatted,
+    )])
+}
+
+/**
+ * Format the statements that overlap with the range, the text between statements is left as it is
+ */
+pub fn handle_range(
+    document: &DocumentData,
+    params: &DocumentRangeFormattingParams,
+) -> Option<Vec<TextEdit>> {
+    let root = document.tree.root_node();
+    if root.has_error() {
+        return None;
+    }
+
+    let mut edits = Vec::new();
+    for statement in root.children(&mut root.walk()) {
+        if statement.kind() != "statement" {
+            continue;
+        }
+
+        let range = to_lsp_range(statement.range());
+        if range.end < params.range.start || range.start > params.range.end {
+            continue;
+        }
+
+        let column = get_start_column(document, &statement);
+        let formatted = format_statement(document, &statement, &params.options, column);
+        if formatted != document.get_source_for_range(statement.range()) {
+            edits.push(TextEdit::new(range, formatted));
+        }
+    }
+
+    Some(edits)
+}
+
+/**
+ * Format a document. Statements are written one after another as in the source, at most one empty line is kept between them.
+ * The line endings of the source are kept, final newlines are only added or trimmed if the options ask for it
+ */
+pub fn format_document(document: &DocumentData, options: &FormattingOptions) -> Option<String> {
+    let root = document.tree.root_node();
+    if root.has_error() {
+        return None;
+    }
+
+    let line_ending = get_line_ending(document);
+    let mut formatted = String::new();
+    let mut previous: Option<Node> = None;
+
+    for child in root.children(&mut root.walk()) {
+        if let Some(previous) = previous {
+            let gap = document
+                .source
+                .byte_slice(previous.end_byte()..child.start_byte())
+                .chars()
+                .filter(|character| *character == '\n')
+                .count();
+
+            match gap {
+                0 => formatted.push(' '),
+                1 => formatted.push_str(line_ending),
+                _ => formatted.push_str(&line_ending.repeat(2)),
+            }
+        }
+
+        if child.kind() == "statement" {
+            let column = formatted
+                .rsplit('\n')
+                .next()
+                .unwrap_or("")
+                .chars()
+                .count();
+            formatted.push_str(&format_statement(document, &child, options, column));
+        } else {
+            // Comments are kept exactly as they are written, a single line comment ends in front of \r\n
+            formatted.push_str(
+                document
+                    .get_source_for_range(child.range())
+                    .trim_end_matches('\r'),
+            );
+        }
+
+        previous = Some(child);
+    }
+
+    // A document without statements and comments is left as it is
+    let last = match previous {
+        Some(last) => last,
+        None => return Some(document.source.to_string()),
+    };
+
+    let mut final_newlines = document
+        .source
+        .byte_slice(last.end_byte()..)
+        .chars()
+        .filter(|character| *character == '\n')
+        .count();
+    if options.trim_final_newlines == Some(true) {
+        final_newlines = final_newlines.min(1);
+    }
+    if options.insert_final_newline == Some(true) {
+        final_newlines = final_newlines.max(1);
+    }
+    formatted.push_str(&line_ending.repeat(final_newlines));
+
+    Some(formatted)
+}
+
+/**
+ * Format a single statement that starts at the column. If it does not fit into a line, the body is written one literal per line
+ * and aggregates with multiple elements are written one element per line, aligned with the first element
+ */
+pub fn format_statement(
+    document: &DocumentData,
+    statement: &Node,
+    options: &FormattingOptions,
+    column: usize,
+) -> String {
+    // Comments inside of a statement are kept exactly as written, so the statement is not touched at all
+    if contains_comment(statement) {
+        return document.get_source_for_range(statement.range());
+    }
+
+    let formatted = break_statement(document, statement, options, column);
+    match get_line_ending(document) {
+        "\n" => formatted,
+        line_ending => formatted.replace('\n', line_ending),
+    }
+}
+
+fn break_statement(
+    document: &DocumentData,
+    statement: &Node,
+    options: &FormattingOptions,
+    column: usize,
+) -> String {
+    let tokens = get_tokens(document, statement);
+    let line = render(&tokens, None);
+    if column + line.chars().count() <= MAX_LINE_LENGTH {
+        return line;
+    }
+
+    // The body starts after :- or :~, statements without a body are only broken at their aggregate elements
+    let has_body = statement
+        .children(&mut statement.walk())
+        .any(|child| matches!(child.kind(), "IF" | "WIF"));
+    let body_start = match tokens
+        .iter()
+        .position(|token| token.text == ":-" || token.text == ":~")
+    {
+        Some(index) if has_body && index + 1 < tokens.len() => index + 1,
+        _ => return render(&tokens, Some(column)),
+    };
+
+    let indent = get_indent(options);
+    let indent_width = get_indent_width(options);
+
+    let mut formatted = render(&tokens[..body_start], Some(column));
+
+    // Only the head was too long, a short body stays behind the :-
+    let body = render(&tokens[body_start..], None);
+    let head_end = formatted.lines().last().unwrap_or("").chars().count();
+    if formatted.contains('\n') && head_end + 1 + body.chars().count() <= MAX_LINE_LENGTH {
+        return format!("{} {}", formatted, body);
+    }
+
+    let mut literal_start = body_start;
+    for index in body_start..tokens.len() {
+        let token = &tokens[index];
+        let is_end = index == tokens.len() - 1;
+        if !token.body_separator && !is_end {
+            continue;
+        }
+
+        formatted.push('\n');
+        formatted.push_str(&indent);
+        formatted.push_str(&render(&tokens[literal_start..index], Some(indent_width)));
+        formatted.push_str(&token.text);
+        literal_start = index + 1;
+    }
+
+    formatted
+}
+
+/**
+ * Write tokens into a line. If the column the line starts at is given and the tokens do not fit into the line,
+ * the elements of aggregates are broken into separate lines
+ */
+fn render(tokens: &[Token], column: Option<usize>) -> String {
+    let mut line = String::new();
+    for (index, token) in tokens.iter().enumerate() {
+        if index > 0 && token.space_before {
+            line.push(' ');
+        }
+        line.push_str(&token.text);
+    }
+
+    let column = match column {
+        Some(column) => column,
+        None => return line,
+    };
+    if column + line.chars().count() <= MAX_LINE_LENGTH
+        || !tokens.iter().any(|token| token.element_separator.is_some())
+    {
+        return line;
+    }
+
+    // The column the first element of every aggregate starts at
+    let mut element_columns: HashMap<usize, usize> = HashMap::new();
+    let mut formatted = String::new();
+    let mut current_column = column;
+    let mut line_break = false;
+
+    for (index, token) in tokens.iter().enumerate() {
+        if index > 0 && token.space_before && !line_break {
+            formatted.push(' ');
+            current_column += 1;
+        }
+        line_break = false;
+
+        if let Some(aggregate) = token.element_start {
+            element_columns.insert(aggregate, current_column);
+        }
+
+        formatted.push_str(&token.text);
+        current_column += token.text.chars().count();
+
+        if let Some(aggregate) = token.element_separator {
+            if let Some(element_column) = element_columns.get(&aggregate) {
+                formatted.push('\n');
+                formatted.push_str(&" ".repeat(*element_column));
+                current_column = *element_column;
+                line_break = true;
+            }
+        }
+    }
+
+    formatted
+}
+
+/**
+ * Collect the tokens of a statement and decide where spaces are written
+ */
+fn get_tokens(document: &DocumentData, statement: &Node) -> Vec<Token> {
+    let mut leaves = Vec::new();
+    collect_leaves(*statement, &mut leaves);
+
+    let mut tokens: Vec<Token> = Vec::new();
+    let mut previous: Option<Node> = None;
+
+    for (index, leaf) in leaves.iter().enumerate() {
+        let parent_kind = leaf.parent().map_or("", |parent| parent.kind());
+
+        let space_before = match previous {
+            Some(previous) => needs_space(document, &previous, leaf),
+            None => false,
+        };
+
+        let element_separator = match leaf.kind() {
+            "SEM" if parent_kind.ends_with("elemvec") => get_aggregate(leaf).map(|node| node.id()),
+            _ => None,
+        };
+
+        let element_start = match index.checked_sub(1).map(|previous| leaves[previous]) {
+            Some(previous) if previous.kind() == "LBRACE" => {
+                previous.parent().map(|aggregate| aggregate.id())
+            }
+            _ => None,
+        };
+
+        tokens.push(Token {
+            text: document.get_source_for_range(leaf.range()),
+            space_before,
+            body_separator: matches!(leaf.kind(), "COMMA" | "SEM")
+                && matches!(parent_kind, "bodydot" | "bodycomma"),
+            element_separator,
+            element_start,
+        });
+
+        previous = Some(*leaf);
+    }
+
+    tokens
+}
+
+/**
+ * Decide if two neighbouring tokens are separated by a space.
+ * Spacing around :-, commas, comparisons, : and braces is normalized, otherwise a space is kept if there was whitespace in the source
+ */
+fn needs_space(document: &DocumentData, previous: &Node, next: &Node) -> bool {
+    let previous_kind = previous.kind();
+    let next_kind = next.kind();
+
+    if matches!(next_kind, "COMMA" | "SEM" | "DOT" | "RPAREN") {
+        return false;
+    }
+    if previous_kind == "LPAREN" {
+        return false;
+    }
+    if next_kind == "LPAREN" && previous_kind == "identifier" {
+        return false;
+    }
+    if previous_kind == "LBRACE" && next_kind == "RBRACE" {
+        return false;
+    }
+
+    if is_spaced(previous) || is_spaced(next) {
+        return true;
+    }
+    if matches!(previous_kind, "COMMA" | "NOT" | "LBRACE" | "RBRACE")
+        || matches!(next_kind, "LBRACE" | "RBRACE")
+    {
+        return true;
+    }
+    if previous_kind == "SEM" && is_separator(previous) {
+        return true;
+    }
+
+    previous.end_byte() < next.start_byte()
+        && document
+            .source
+            .byte_slice(previous.end_byte()..next.start_byte())
+            .chars()
+            .any(char::is_whitespace)
+}
+
+/**
+ * Tokens that are surrounded by a single space, e.g. :- and comparison operators
+ */
+fn is_spaced(node: &Node) -> bool {
+    matches!(node.kind(), "IF" | "WIF" | "COLON" | "EQ")
+        || node.parent().is_some_and(|parent| parent.kind() == "cmp")
+}
+
+/**
+ * Check if a ; separates body literals, aggregate elements or the atoms of a disjunction, and not the terms of a pool
+ */
+fn is_separator(node: &Node) -> bool {
+    node.parent().is_some_and(|parent| {
+        matches!(
+            parent.kind(),
+            "bodydot" | "bodycomma" | "disjunction" | "disjunctionsep"
+        ) || parent.kind().ends_with("elemvec")
+    })
+}
+
+/**
+ * The node that encloses the elements of an aggregate with braces
+ */
+fn get_aggregate<'a>(node: &Node<'a>) -> Option<Node<'a>> {
+    let mut current = node.parent();
+    while let Some(parent) = current {
+        if parent
+            .children(&mut parent.walk())
+            .any(|child| child.kind() == "LBRACE")
+        {
+            return Some(parent);
+        }
+        current = parent.parent();
+    }
+
+    None
+}
+
+fn collect_leaves<'a>(node: Node<'a>, leaves: &mut Vec<Node<'a>>) {
+    // Strings are written as they are, including their escape sequences
+    if node.child_count() == 0 || node.kind() == "STRING" {
+        leaves.push(node);
+        return;
+    }
+
+    for child in node.children(&mut node.walk()) {
+        collect_leaves(child, leaves);
+    }
+}
+
+/**
+ * The column a node starts at in characters, which is where its formatted text starts as well
+ */
+fn get_start_column(document: &DocumentData, node: &Node) -> usize {
+    let line_start = document.source.line_to_byte(node.start_position().row);
+    document
+        .source
+        .byte_slice(line_start..node.start_byte())
+        .len_chars()
+}
+
+/**
+ * Documents that end their first line with \r\n keep these line endings
+ */
+fn get_line_ending(document: &DocumentData) -> &'static str {
+    let first_line = document.source.line(0);
+    let length = first_line.len_chars();
+    if length >= 2 && first_line.char(length - 2) == '\r' && first_line.char(length - 1) == '\n' {
+        "\r\n"
+    } else {
+        "\n"
+    }
+}
+
+fn contains_comment(node: &Node) -> bool {
+    node.children(&mut node.walk()).any(|child| {
+        matches!(child.kind(), "single_comment" | "multi_comment") || contains_comment(&child)
+    })
+}
+
+fn get_indent(options: &FormattingOptions) -> String {
+    if options.insert_spaces {
+        " ".repeat(options.tab_size as usize)
+    } else {
+        "\t".to_string()
+    }
+}
+
+fn get_indent_width(options: &FormattingOptions) -> usize {
+    options.tab_size as usize
+}
+
+#[cfg(test)]
+fn format_test_source(source: &str) -> String {
+    let document = create_test_document(source.to_string());
+    let options = FormattingOptions {
+        tab_size: 4,
+        insert_spaces: true,
+        ..Default::default()
+    };
+    let formatted = format_document(&document, &options).unwrap();
+
+    // Formatting has to be idempotent
+    let reformatted = format_document(&create_test_document(formatted.clone()), &options).unwrap();
+    assert_eq!(formatted, reformatted);
+
+    formatted
+}
+
+#[test]
+fn spacing_should_be_normalized() {
+    assert_eq!(
+        format_test_source("a(X,Y):-b(X),not c(Y),X!=Y,  X<Y.\n"),
+        "a(X, Y) :- b(X), not c(Y), X != Y, X < Y.\n"
+    );
+    assert_eq!(
+        format_test_source(":-  #count{X:p(X)}>2.\n{a(X):b(X);c}=1."),
+        ":- #count { X : p(X) } > 2.\n{ a(X) : b(X); c } = 1."
+    );
+    assert_eq!(
+        format_test_source("p(1;2). q(X+1) :- p(X), X = 1..2.\n#const n=3.\n"),
+        "p(1;2). q(X+1) :- p(X), X = 1..2.\n#const n = 3.\n"
+    );
+}
+
+#[test]
+fn comments_should_be_preserved() {
+    let source = "%*! a/1\n * Documented *%\na :- b,    % why\n   c.\n\n\n\n% between\nb.   % trailing\nc.\n";
+
+    assert_eq!(
+        format_test_source(source),
+        "%*! a/1\n * Documented *%\na :- b,    % why\n   c.\n\n% between\nb. % trailing\nc.\n"
+    );
+}
+
+#[test]
+fn long_bodies_should_be_broken_into_one_literal_per_line() {
+    let formatted = format_test_source(
+        "reachable(X,Y) :- edge(X,Z), reachable(Z,Y), not blocked(X), not blocked(Y), X != Y.\n",
+    );
+
+    assert_eq!(
+        formatted,
+        "reachable(X, Y) :-
+    edge(X, Z),
+    reachable(Z, Y),
+    not blocked(X),
+    not blocked(Y),
+    X != Y.\n"
+    );
+}
+
+#[test]
+fn line_length_should_be_measured_from_the_start_of_the_line() {
+    let formatted = format_test_source(
+        "fact(1). fact(2). reachable(X,Y) :- edge(X,Z), reachable(Z,Y), not blocked(X), X != Y.\n",
+    );
+
+    assert_eq!(
+        formatted,
+        "fact(1). fact(2). reachable(X, Y) :-
+    edge(X, Z),
+    reachable(Z, Y),
+    not blocked(X),
+    X != Y.\n"
+    );
+}
+
+#[test]
+fn long_weak_constraints_should_keep_their_weight_behind_the_last_literal() {
+    let formatted = format_test_source(
+        ":~ assign(X,Y), cost(X,Y,C), preference(X,P), something_else_long(Y,Q), more(Q). [C@P,X,Y]\n",
+    );
+
+    assert_eq!(
+        formatted,
+        ":~
+    assign(X, Y),
+    cost(X, Y, C),
+    preference(X, P),
+    something_else_long(Y, Q),
+    more(Q). [C@P, X, Y]\n"
+    );
+}
+
+#[test]
+fn aggregate_elements_should_be_aligned() {
+    let formatted = format_test_source(
+        ":- #sum { W,X : weight(X,W), selected(X); V,Y : value(Y,V), selected(Y); 1,z : extra } > limit, budget(limit).\n",
+    );
+
+    assert_eq!(
+        formatted,
+        ":-
+    #sum { W, X : weight(X, W), selected(X);
+           V, Y : value(Y, V), selected(Y);
+           1, z : extra } > limit,
+    budget(limit).\n"
+    );
+
+    let formatted = format_test_source(
+        "{ assign(X,Y) : first_domain(X), second_domain(Y); unassigned(X) : first_domain(X) } = 1 :- go.",
+    );
+
+    assert_eq!(
+        formatted,
+        "{ assign(X, Y) : first_domain(X), second_domain(Y);
+  unassigned(X) : first_domain(X) } = 1 :- go."
+    );
+}
+
+#[test]
+fn final_newlines_should_only_change_if_the_options_ask_for_it() {
+    let format = |source: &str, insert: Option<bool>, trim: Option<bool>| {
+        let options = FormattingOptions {
+            insert_final_newline: insert,
+            trim_final_newlines: trim,
+            ..Default::default()
+        };
+        format_document(&create_test_document(source.to_string()), &options).unwrap()
+    };
+
+    assert_eq!(format("a.\n", Some(false), None), "a.\n");
+    assert_eq!(format("a.", None, None), "a.");
+    assert_eq!(format("a.", Some(true), None), "a.\n");
+    assert_eq!(format("a.\n\n\n", None, None), "a.\n\n\n");
+    assert_eq!(format("a.\n\n\n", None, Some(true)), "a.\n");
+}
+
+#[test]
+fn line_endings_should_be_kept() {
+    assert_eq!(
+        format_test_source("a:-b.\r\nc. % note\r\n\r\n\r\nd.\r\n"),
+        "a :- b.\r\nc. % note\r\n\r\nd.\r\n"
+    );
+    assert_eq!(
+        format_test_source(
+            "reachable(X,Y) :- edge(X,Z), reachable(Z,Y), not blocked(X), not blocked(Y), X != Y.\r\n"
+        ),
+        "reachable(X, Y) :-\r\n    edge(X, Z),\r\n    reachable(Z, Y),\r\n    not blocked(X),\r\n    not blocked(Y),\r\n    X != Y.\r\n"
+    );
+}
+
+#[test]
+fn documents_with_syntax_errors_should_not_be_formatted() {
+    let document = create_test_document("a :- b".to_string());
+
+    assert_eq!(
+        format_document(&document, &FormattingOptions::default()),
+        None
+    );
+}
+
+#[test]
+fn range_formatting_should_only_change_the_statements_in_the_range() {
+    let document = create_test_document("a:-b.\nc:-d.\ne:-f.".to_string());
+
+    let edits = handle_range(
+        &document,
+        &DocumentRangeFormattingParams {
+            text_document: tower_lsp::lsp_types::TextDocumentIdentifier::new(document.uri.clone()),
+            range: Range::new(Position::new(1, 0), Position::new(1, 2)),
+            options: FormattingOptions::default(),
+            work_done_progress_params: Default::default(),
+        },
+    )
+    .unwrap();
+
+    assert_eq!(
+        edits,
+        vec![TextEdit::new(
+            Range::new(Position::new(1, 0), Position::new(1, 5)),
+            "c :- d.".to_string()
+        )]
+    );
+}
@@ -0,0 +1,209 @@
+use tower_lsp::lsp_types::{DocumentOnTypeFormattingParams, Position, Range, TextEdit};
+use tree_sitter::{Node, Point};
+
+use crate::document::DocumentData;
+
+use super::{contains_comment, format_statement, get_start_column, get_tokens, render};
+
+#[cfg(test)]
+use crate::test_utils::create_test_document;
+#[cfg(test)]
+use tower_lsp::lsp_types::FormattingOptions;
+
+/**
+ * The character that triggers on type formatting first, it completes a statement
+ */
+pub const FIRST_TRIGGER_CHARACTER: &str = ".";
+
+/**
+ * The - of :- completes the head of a rule
+ */
+pub const MORE_TRIGGER_CHARACTERS: [&str; 1] = ["-"];
+
+/**
+ * Format the statement that was completed with a '.' or the head of the rule that was completed with ':-'.
+ * Only the nodes around the position are looked at, the tree is the one kept up to date by update_document,
+ * so nothing has to be parsed again while typing
+ */
+pub fn handle(
+    document: &DocumentData,
+    params: &DocumentOnTypeFormattingParams,
+) -> Option<Vec<TextEdit>> {
+    let position = params.text_document_position.position;
+
+    // The token that ends with the typed character, e.g. not the first . of 1..3.
+    // The position counts characters, the tree counts bytes
+    let line = document.source.get_line(position.line as usize)?;
+    let end = Point {
+        row: position.line as usize,
+        column: line.try_char_to_byte(position.character as usize).ok()?,
+    };
+    let start = Point {
+        row: end.row,
+        column: end.column.checked_sub(1)?,
+    };
+    let typed = document
+        .tree
+        .root_node()
+        .descendant_for_point_range(start, end)?;
+    if typed.end_position() != end {
+        return None;
+    }
+
+    let edit = match (params.ch.as_str(), typed.kind()) {
+        (".", "DOT") => format_completed_statement(document, &typed, params),
+        ("-", "IF") => format_rule_head(document, &typed),
+        _ => None,
+    };
+
+    Some(edit.into_iter().collect())
+}
+
+/**
+ * Format the statement that ends with the dot, statements with syntax errors are left as they are
+ */
+fn format_completed_statement(
+    document: &DocumentData,
+    dot: &Node,
+    params: &DocumentOnTypeFormattingParams,
+) -> Option<TextEdit> {
+    let mut statement = dot.parent()?;
+    while statement.kind() != "statement" {
+        statement = statement.parent()?;
+    }
+
+    if statement.end_byte() != dot.end_byte() || statement.has_error() {
+        return None;
+    }
+
+    let column = get_start_column(document, &statement);
+    let formatted = format_statement(document, &statement, &params.options, column);
+    if formatted == document.get_source_for_range(statement.range()) {
+        return None;
+    }
+
+    Some(TextEdit::new(
+        to_character_range(document, statement.range()),
+        formatted,
+    ))
+}
+
+/**
+ * Format the head in front of :-, the body is not written yet and is therefore not touched
+ */
+fn format_rule_head(document: &DocumentData, if_node: &Node) -> Option<TextEdit> {
+    let head = if_node.prev_sibling()?;
+    if head.kind() != "head" || head.has_error() || contains_comment(&head) {
+        return None;
+    }
+
+    let range = tree_sitter::Range {
+        start_byte: head.start_byte(),
+        end_byte: if_node.end_byte(),
+        start_point: head.start_position(),
+        end_point: if_node.end_position(),
+    };
+
+    let formatted = format!("{} :-", render(&get_tokens(document, &head), None));
+    if formatted == document.get_source_for_range(range) {
+        return None;
+    }
+
+    Some(TextEdit::new(to_character_range(document, range), formatted))
+}
+
+/**
+ * The edits are sent in characters, like the position of the typed character
+ */
+fn to_character_range(document: &DocumentData, range: tree_sitter::Range) -> Range {
+    let to_position = |byte: usize| {
+        let line = document.source.byte_to_line(byte);
+        let character = document.source.byte_to_char(byte) - document.source.line_to_char(line);
+        Position::new(line as u32, character as u32)
+    };
+
+    Range::new(to_position(range.start_byte), to_position(range.end_byte))
+}
+
+#[cfg(test)]
+fn format_on_type(document: &DocumentData, line: u32, character: u32, ch: &str) -> Vec<TextEdit> {
+    use tower_lsp::lsp_types::{TextDocumentIdentifier, TextDocumentPositionParams};
+
+    handle(
+        document,
+        &DocumentOnTypeFormattingParams {
+            text_document_position: TextDocumentPositionParams {
+                text_document: TextDocumentIdentifier::new(document.uri.clone()),
+                position: Position::new(line, character),
+            },
+            ch: ch.to_string(),
+            options: FormattingOptions {
+                tab_size: 4,
+                insert_spaces: true,
+                ..Default::default()
+            },
+        },
+    )
+    .unwrap_or_default()
+}
+
+#[test]
+fn completed_statement_should_be_formatted() {
+    let document = create_test_document("b.\na(X,Y):-b,c(X),d(Y).".to_string());
+
+    assert_eq!(
+        format_on_type(&document, 1, 20, "."),
+        vec![TextEdit::new(
+            Range::new(Position::new(1, 0), Position::new(1, 20)),
+            "a(X, Y) :- b, c(X), d(Y).".to_string()
+        )]
+    );
+
+    // The dots of an interval do not complete a statement, a formatted statement is not changed
+    let document = create_test_document("a(X) :- X = 1..3.".to_string());
+    assert!(format_on_type(&document, 0, 15, ".").is_empty());
+    assert!(format_on_type(&document, 0, 17, ".").is_empty());
+}
+
+#[test]
+fn head_should_be_formatted_when_typing_if() {
+    let mut document = create_test_document("b.\n".to_string());
+    let mut parser = tree_sitter::Parser::new();
+    parser.set_language(tree_sitter_clingo::language()).unwrap();
+
+    // Typing goes through the incremental update of the document
+    document.update_document(
+        vec![tower_lsp::lsp_types::TextDocumentContentChangeEvent {
+            range: Some(Range::new(Position::new(1, 0), Position::new(1, 0))),
+            range_length: None,
+            text: "a(X,Y):-".to_string(),
+        }],
+        &mut parser,
+    );
+
+    assert_eq!(
+        format_on_type(&document, 1, 8, "-"),
+        vec![TextEdit::new(
+            Range::new(Position::new(1, 0), Position::new(1, 8)),
+            "a(X, Y) :-".to_string()
+        )]
+    );
+
+    // The position counts characters, ä is two bytes long
+    let document = create_test_document("b.\na(\"ä\",Y):-".to_string());
+    assert_eq!(
+        format_on_type(&document, 1, 10, "-"),
+        vec![TextEdit::new(
+            Range::new(Position::new(1, 0), Position::new(1, 10)),
+            "a(\"ä\", Y) :-".to_string()
+        )]
+    );
+
+    // A comment in the head is kept, the head is not touched
+    let document = create_test_document("a(X, % note\n  Y):-".to_string());
+    assert!(format_on_type(&document, 1, 6, "-").is_empty());
+
+    // A - that is no :- is ignored
+    let document = create_test_document("a :- b(X), X = -".to_string());
+    assert!(format_on_type(&document, 0, 16, "-").is_empty());
+}
@@ -3,6 +3,7 @@ use tree_sitter::Point;
 
 use crate::{
     document::DocumentData, semantics::predicate_occurence_semantics::PredicateOccurenceLocation,
+    workspace::program::Program,
 };
 
 use super::get_occurences_for_predicate;
@@ -10,7 +11,11 @@ use super::get_occurences_for_predicate;
 /**
  * Check and find the definition for an predicate at this position
  */
-pub fn check_goto_definition(document: &DocumentData, position: Position) -> Option<Vec<Location>> {
+pub fn check_goto_definition(
+    document: &DocumentData,
+    program: &Program,
+    position: Position,
+) -> Option<Vec<Location>> {
     || -> Option<Vec<Location>> {
         let node = document.tree.root_node().descendant_for_point_range(
             Point {
@@ -23,9 +28,34 @@ pub fn check_goto_definition(document: &DocumentData, position: Position) -> Opt
             },
         );
 
-        let ret =
-            get_occurences_for_predicate(document, node, vec![PredicateOccurenceLocation::Head]);
+        let ret = get_occurences_for_predicate(
+            document,
+            program,
+            node,
+            vec![PredicateOccurenceLocation::Head],
+        );
 
         Some(ret)
     }()
 }
+
+#[cfg(test)]
+use crate::test_utils::create_test_document;
+#[cfg(test)]
+use std::str::FromStr;
+#[cfg(test)]
+use tower_lsp::lsp_types::Url;
+
+#[test]
+fn definition_should_be_found_in_other_documents() {
+    let encoding = create_test_document("b(X) :- a(X).".to_string());
+    let instance_uri = Url::from_str("file:///instance.lp").unwrap();
+    let instance = DocumentData::from_source(instance_uri.clone(), "\na(1).", 1);
+    let program = Program::new(vec![encoding.clone(), instance]);
+
+    let locations = check_goto_definition(&encoding, &program, Position::new(0, 8)).unwrap();
+
+    assert_eq!(locations.len(), 1);
+    assert_eq!(locations[0].uri, instance_uri);
+    assert_eq!(locations[0].range.start, Position::new(1, 0));
+}
@@ -3,19 +3,21 @@ use tree_sitter::Node;
 
 use crate::{
     document::DocumentData, semantics::predicate_occurence_semantics::PredicateOccurenceLocation,
+    workspace::program::Program,
 };
 
 pub mod definition;
 pub mod references;
 
 /**
- * Obtain the occurences for a specific predicate
+ * Obtain the occurences for a specific predicate in every document of the program
  * identifier: The identifier of this predicate
  * arity: The arity of this predicate
  * locations: Which location the predicate needs to be to be counted as an occurence
  */
 pub fn get_occurences_for_predicate(
     document: &DocumentData,
+    program: &Program,
     starting_node: Option<Node>,
     locations: Vec<PredicateOccurenceLocation>,
 ) -> Vec<Location> {
@@ -35,8 +37,7 @@ pub fn get_occurences_for_predicate(
                 .get_predicates_arity_for_node(&node.unwrap().child(2).unwrap().id())
                 + 1;
 
-            for ((identifier, arity), occurences) in
-                document.semantics.predicate_semantics.predicates.clone()
+            for ((identifier, arity), occurences) in program.predicate_semantics.predicates.clone()
             {
                 // Find if this is the correct identifier and arity
                 if identifier == node_identifier && arity == node_arity {
@@ -54,7 +55,7 @@ pub fn get_occurences_for_predicate(
                                 },
                             );
 
-                            ret.push(Location::new(document.uri.clone(), range));
+                            ret.push(Location::new(occurence.uri, range));
                         }
                     }
 
@@ -3,6 +3,7 @@ use tree_sitter::Point;
 
 use crate::{
     document::DocumentData, semantics::predicate_occurence_semantics::PredicateOccurenceLocation,
+    workspace::program::Program,
 };
 
 use super::get_occurences_for_predicate;
@@ -10,7 +11,11 @@ use super::get_occurences_for_predicate;
 /**
  * Check and find the references to an predicate at this position
  */
-pub fn check_goto_references(document: &DocumentData, position: Position) -> Option<Vec<Location>> {
+pub fn check_goto_references(
+    document: &DocumentData,
+    program: &Program,
+    position: Position,
+) -> Option<Vec<Location>> {
     || -> Option<Vec<Location>> {
         let node = document.tree.root_node().descendant_for_point_range(
             Point {
@@ -25,6 +30,7 @@ pub fn check_goto_references(document: &DocumentData, position: Position) -> Opt
 
         let ret = get_occurences_for_predicate(
             document,
+            program,
             node,
             vec![
                 PredicateOccurenceLocation::Body,
@@ -35,3 +41,25 @@ pub fn check_goto_references(document: &DocumentData, position: Position) -> Opt
         Some(ret)
     }()
 }
+
+#[cfg(test)]
+use crate::test_utils::create_test_document;
+#[cfg(test)]
+use std::str::FromStr;
+#[cfg(test)]
+use tower_lsp::lsp_types::Url;
+
+#[test]
+fn references_should_be_found_in_other_documents() {
+    let instance = create_test_document("a(1).".to_string());
+    let encoding_uri = Url::from_str("file:///encoding.lp").unwrap();
+    let encoding = DocumentData::from_source(encoding_uri.clone(), "b(X) :- a(X), c : a(X).", 1);
+    let program = Program::new(vec![instance.clone(), encoding]);
+
+    let locations = check_goto_references(&instance, &program, Position::new(0, 0)).unwrap();
+
+    assert_eq!(locations.len(), 2);
+    assert!(locations
+        .iter()
+        .all(|location| location.uri == encoding_uri));
+}
@@ -1,4 +1,6 @@
 use std::borrow::Cow;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::{Arc, RwLock};
 use std::time::Instant;
 
 use completion::check_completion;
@@ -14,17 +16,30 @@ use tower_lsp::lsp_types::notification::Notification;
 use tower_lsp::lsp_types::*;
 use tower_lsp::{Client, LanguageServer, LspService, Server};
 
+use settings::Settings;
 use tree_sitter::{Node, Parser};
+use workspace::Workspace;
 
+mod call_hierarchy;
+mod cli;
+mod code_actions;
+mod commands;
 mod completion;
 mod diagnostics;
 mod document;
 mod goto;
 mod semantics;
 mod documentation;
+mod folding_range;
+mod formatting;
 mod hover;
+mod rename;
+mod semantic_tokens;
+mod settings;
 mod utils;
 mod signature_help;
+mod symbols;
+mod workspace;
 
 #[cfg(test)]
 mod test_utils;
@@ -44,11 +59,125 @@ fn print_tree(node: Node,source:&[u8], depth: usize) {
 struct Backend {
     client: Client,
     document_map: DashMap<String, DocumentData>,
+    workspace: Arc<Workspace>,
+    semantic_tokens: DashMap<String, SemanticTokens>,
+    settings: RwLock<Settings>,
+
+    /**
+     * If the client supports workspace/configuration the settings are requested instead of relying on notifications
+     */
+    configuration_capability: AtomicBool,
+}
+
+impl Backend {
+    /**
+     * Use new settings, the include paths are passed on to the workspace
+     */
+    fn apply_settings(&self, settings: Settings) {
+        self.workspace
+            .set_include_paths(settings.include_paths.clone());
+        *self.settings.write().unwrap() = settings;
+    }
+
+    /**
+     * Request the settings from the client with workspace/configuration
+     */
+    async fn fetch_settings(&self) -> Option<Settings> {
+        let values = match self
+            .client
+            .configuration(vec![ConfigurationItem {
+                scope_uri: None,
+                section: Some(settings::SECTION.to_string()),
+            }])
+            .await
+        {
+            Ok(values) => values,
+            Err(error) => {
+                info!("Could not request the configuration: {:?}", error);
+                return None;
+            }
+        };
+
+        match Settings::from_value(values.first()?) {
+            Ok(settings) => Some(settings),
+            Err(error) => {
+                self.client
+                    .log_message(MessageType::ERROR, format!("Invalid settings: {}", error))
+                    .await;
+                None
+            }
+        }
+    }
+
+    /**
+     * Run the diagnostics for a document and send them to the client
+     */
+    async fn publish_diagnostics(&self, document: DocumentData) {
+        let time = Instant::now();
+        let uri = document.uri.clone();
+        let program = self.workspace.get_root_program(&uri, &self.document_map);
+        let settings = self.settings.read().unwrap().clone();
+        let diagnostics = run_diagnostics(
+            document,
+            &program,
+            &settings.diagnostics,
+            settings.maximum_number_of_problems,
+        );
+        self.client
+            .publish_diagnostics(uri, diagnostics, Some(1))
+            .await;
+        let duration = time.elapsed();
+        info!("Time needed for diagnostics: {:?}", duration);
+    }
+
+    /**
+     * Run the diagnostics for every open document again, e.g. after the settings changed
+     */
+    async fn publish_all_diagnostics(&self) {
+        let documents: Vec<DocumentData> = self
+            .document_map
+            .iter()
+            .map(|refmulti| refmulti.value().clone())
+            .collect();
+
+        for document in documents {
+            self.publish_diagnostics(document).await;
+        }
+    }
 }
 
 #[tower_lsp::async_trait]
 impl LanguageServer for Backend {
-    async fn initialize(&self, _: InitializeParams) -> Result<InitializeResult> {
+    async fn initialize(&self, params: InitializeParams) -> Result<InitializeResult> {
+        // Remember the workspace folders, these are used to resolve #include statements
+        let mut folders: Vec<Url> = params
+            .workspace_folders
+            .unwrap_or_default()
+            .into_iter()
+            .map(|folder| folder.uri)
+            .collect();
+        if folders.is_empty() {
+            folders.extend(params.root_uri);
+        }
+        self.workspace.set_folders(folders);
+
+        // The settings can be given with the initialization options, e.g. {"diagnostics": {"arityMismatch": "warning"}}
+        if let Some(options) = params.initialization_options.as_ref() {
+            match Settings::from_value(options) {
+                Ok(settings) => self.apply_settings(settings),
+                Err(error) => info!("Invalid initialization options: {}", error),
+            }
+        }
+
+        let configuration_capability = params
+            .capabilities
+            .workspace
+            .as_ref()
+            .and_then(|workspace| workspace.configuration)
+            .unwrap_or(false);
+        self.configuration_capability
+            .store(configuration_capability, Ordering::Relaxed);
+
         Ok(InitializeResult {
             server_info: None,
             capabilities: ServerCapabilities {
@@ -64,6 +193,10 @@ impl LanguageServer for Backend {
                 }),
                 definition_provider: Some(OneOf::Left(true)),
                 references_provider: Some(OneOf::Left(true)),
+                rename_provider: Some(OneOf::Right(RenameOptions {
+                    prepare_provider: Some(true),
+                    work_done_progress_options: Default::default(),
+                })),
                 workspace: Some(WorkspaceServerCapabilities {
                     workspace_folders: Some(WorkspaceFoldersServerCapabilities {
                         supported: Some(true),
@@ -78,9 +211,46 @@ impl LanguageServer for Backend {
                         work_done_progress: None,
                     },
                 }),
+                document_symbol_provider: Some(OneOf::Left(true)),
+                workspace_symbol_provider: Some(OneOf::Left(true)),
+                semantic_tokens_provider: Some(
+                    SemanticTokensServerCapabilities::SemanticTokensOptions(
+                        SemanticTokensOptions {
+                            legend: semantic_tokens::get_legend(),
+                            full: Some(SemanticTokensFullOptions::Delta { delta: Some(true) }),
+                            range: None,
+                            ..Default::default()
+                        },
+                    ),
+                ),
                 hover_provider: Some(
                     HoverProviderCapability::Simple(true),
                 ),
+                call_hierarchy_provider: Some(CallHierarchyServerCapability::Simple(true)),
+                execute_command_provider: Some(ExecuteCommandOptions {
+                    commands: commands::get_commands(),
+                    work_done_progress_options: Default::default(),
+                }),
+                code_action_provider: Some(CodeActionProviderCapability::Options(
+                    CodeActionOptions {
+                        code_action_kinds: Some(vec![CodeActionKind::QUICKFIX]),
+                        work_done_progress_options: Default::default(),
+                        resolve_provider: None,
+                    },
+                )),
+                document_formatting_provider: Some(OneOf::Left(true)),
+                document_range_formatting_provider: Some(OneOf::Left(true)),
+                folding_range_provider: Some(FoldingRangeProviderCapability::Simple(true)),
+                document_on_type_formatting_provider: Some(DocumentOnTypeFormattingOptions {
+                    first_trigger_character: formatting::on_type::FIRST_TRIGGER_CHARACTER
+                        .to_string(),
+                    more_trigger_character: Some(
+                        formatting::on_type::MORE_TRIGGER_CHARACTERS
+                            .iter()
+                            .map(|character| character.to_string())
+                            .collect(),
+                    ),
+                }),
                 ..ServerCapabilities::default()
             },
         })
@@ -91,28 +261,138 @@ impl LanguageServer for Backend {
             .log_message(MessageType::INFO, "initialized!")
             .await;
 
+        // Ask the client to tell us when encodings change on disk
+        let registration = Registration {
+            id: "watch-encodings".to_string(),
+            method: "workspace/didChangeWatchedFiles".to_string(),
+            register_options: serde_json::to_value(DidChangeWatchedFilesRegistrationOptions {
+                watchers: vec![FileSystemWatcher {
+                    glob_pattern: GlobPattern::String("**/*.lp".to_string()),
+                    kind: None,
+                }],
+            })
+            .ok(),
+        };
+        if let Err(error) = self.client.register_capability(vec![registration]).await {
+            info!("Could not register file watchers: {:?}", error);
+        }
+
+        if self.configuration_capability.load(Ordering::Relaxed) {
+            // Ask the client to tell us when the settings change
+            let registration = Registration {
+                id: "watch-configuration".to_string(),
+                method: "workspace/didChangeConfiguration".to_string(),
+                register_options: None,
+            };
+            if let Err(error) = self.client.register_capability(vec![registration]).await {
+                info!("Could not register for configuration changes: {:?}", error);
+            }
+
+            if let Some(settings) = self.fetch_settings().await {
+                self.apply_settings(settings);
+                self.publish_all_diagnostics().await;
+            }
+        }
+
+        // Read all encodings in the workspace in the background
+        let workspace = self.workspace.clone();
+        tokio::task::spawn_blocking(move || workspace.index_folders(workspace.get_folders()));
     }
 
     async fn shutdown(&self) -> Result<()> {
         Ok(())
     }
 
-    async fn did_change_workspace_folders(&self, _: DidChangeWorkspaceFoldersParams) {
+    async fn did_change_workspace_folders(&self, params: DidChangeWorkspaceFoldersParams) {
         self.client
             .log_message(MessageType::INFO, "workspace folders changed!")
             .await;
+
+        self.workspace.remove_folders(
+            params
+                .event
+                .removed
+                .into_iter()
+                .map(|folder| folder.uri)
+                .collect(),
+        );
+        let added = self.workspace.add_folders(
+            params
+                .event
+                .added
+                .into_iter()
+                .map(|folder| folder.uri)
+                .collect(),
+        );
+
+        let workspace = self.workspace.clone();
+        tokio::task::spawn_blocking(move || workspace.index_folders(added));
     }
 
-    async fn did_change_configuration(&self, _: DidChangeConfigurationParams) {
+    async fn did_change_configuration(&self, params: DidChangeConfigurationParams) {
         self.client
             .log_message(MessageType::INFO, "configuration changed!")
             .await;
+
+        // Clients that support workspace/configuration usually do not send the settings with the notification
+        let settings = if self.configuration_capability.load(Ordering::Relaxed) {
+            self.fetch_settings().await
+        } else {
+            match Settings::from_value(&params.settings) {
+                Ok(settings) => Some(settings),
+                Err(error) => {
+                    self.client
+                        .log_message(MessageType::ERROR, format!("Invalid settings: {}", error))
+                        .await;
+                    None
+                }
+            }
+        };
+
+        if let Some(settings) = settings {
+            self.apply_settings(settings);
+            self.publish_all_diagnostics().await;
+        }
     }
 
-    async fn did_change_watched_files(&self, _: DidChangeWatchedFilesParams) {
+    async fn did_change_watched_files(&self, params: DidChangeWatchedFilesParams) {
         self.client
             .log_message(MessageType::INFO, "watched files have changed!")
             .await;
+
+        // Open documents that are analyzed together with a changed file are found before the change, a deleted file is not included afterwards
+        let changed: Vec<&Url> = params.changes.iter().map(|change| &change.uri).collect();
+        let documents: Vec<DocumentData> = self
+            .document_map
+            .iter()
+            .map(|refmulti| refmulti.value().clone())
+            .collect();
+        let affected: Vec<DocumentData> = documents
+            .into_iter()
+            .filter(|document| {
+                self.workspace
+                    .get_root_program(&document.uri, &self.document_map)
+                    .documents
+                    .iter()
+                    .any(|included| {
+                        included.uri != document.uri && changed.contains(&&included.uri)
+                    })
+            })
+            .collect();
+
+        for change in &params.changes {
+            if change.typ == FileChangeType::DELETED {
+                self.workspace.invalidate_file(&change.uri);
+            } else if !self.document_map.contains_key(&change.uri.to_string()) {
+                // Open documents are kept up to date by the editor, only reread the others
+                self.workspace.load_file(&change.uri);
+            }
+        }
+        self.workspace.invalidate_programs();
+
+        for document in affected {
+            self.publish_diagnostics(document).await;
+        }
     }
 
     async fn did_open(&self, params: DidOpenTextDocumentParams) {
@@ -148,21 +428,13 @@ impl LanguageServer for Backend {
         doc.generate_semantics(None);
         self.document_map
             .insert(params.text_document.uri.to_string(), doc.clone());
-
-        
+        self.workspace.invalidate_programs();
 
         // Run diagnostics for that file
-        let time = Instant::now();
-        let diagnostics = run_diagnostics(doc, 100);
-        self.client
-            .publish_diagnostics(params.text_document.uri.clone(), diagnostics, Some(1))
-            .await;
-        let duration = time.elapsed();
-        info!("Time needed for diagnostics: {:?}", duration);
+        self.publish_diagnostics(doc).await;
     }
 
     async fn did_change(&self, params: DidChangeTextDocumentParams) {
-        let client_copy = self.client.clone();
         let uri = params.text_document.uri.clone().to_string();
 
         if !self.document_map.contains_key(&uri) {
@@ -186,20 +458,18 @@ impl LanguageServer for Backend {
         let doc = document.clone();
 
         self.document_map.insert(uri, document);
+        self.workspace.invalidate_programs();
 
-        let time = Instant::now();
-        let diagnostics = run_diagnostics(doc, 100);
-        client_copy
-            .publish_diagnostics(params.text_document.uri.clone(), diagnostics, Some(1))
-            .await;
-        let duration = time.elapsed();
-        info!("Time needed for diagnostics: {:?}", duration);
+        self.publish_diagnostics(doc).await;
     }
 
-    async fn did_save(&self, _: DidSaveTextDocumentParams) {
+    async fn did_save(&self, params: DidSaveTextDocumentParams) {
         self.client
             .log_message(MessageType::INFO, "file saved!")
             .await;
+
+        // The file on disk changed, so any copy read before is outdated
+        self.workspace.reload_file(&params.text_document.uri);
     }
 
     async fn did_close(&self, params: DidCloseTextDocumentParams) {
@@ -221,6 +491,10 @@ impl LanguageServer for Backend {
 
         // Remove our information for this file
         self.document_map.remove(&uri);
+        self.semantic_tokens.remove(&uri);
+
+        // The file is still part of the workspace, from now on as it is on disk
+        self.workspace.reload_file(&params.text_document.uri);
     }
 
     async fn completion(&self, params: CompletionParams) -> Result<Option<CompletionResponse>> {
@@ -228,7 +502,9 @@ impl LanguageServer for Backend {
         let position = params.text_document_position.position;
 
         let completions = || -> Option<Vec<CompletionItem>> {
-            let document = self.document_map.get(&uri.to_string())?;
+            // The guard is released before the program reads the open documents again
+            let document = self.document_map.get(&uri.to_string())?.value().clone();
+            let program = self.workspace.get_program(&uri, &self.document_map);
 
             if let Some(context) = params.context {
                 let mut trigger_character = "".to_string();
@@ -236,7 +512,13 @@ impl LanguageServer for Backend {
                     trigger_character = trigger;
                 }
 
-                return check_completion(document.value(), context, trigger_character, position);
+                return check_completion(
+                    &document,
+                    &program,
+                    context,
+                    trigger_character,
+                    position,
+                );
             }
 
             Some(vec![])
@@ -251,9 +533,16 @@ impl LanguageServer for Backend {
     ) -> Result<Option<GotoDefinitionResponse>> {
         let uri = params.text_document_position_params.text_document.uri;
         let position = params.text_document_position_params.position;
-        if let Some(document) = self.document_map.get(&uri.to_string()) {
+        let document = self
+            .document_map
+            .get(&uri.to_string())
+            .map(|document| document.value().clone());
+        if let Some(document) = document {
+            let program = self
+                .workspace
+                .get_workspace_program(&uri, &self.document_map);
             return Ok(Some(GotoDefinitionResponse::Array(
-                check_goto_definition(document.value(), position).unwrap(),
+                check_goto_definition(&document, &program, position).unwrap(),
             )));
         }
 
@@ -265,8 +554,15 @@ impl LanguageServer for Backend {
     async fn references(&self, params: ReferenceParams) -> Result<Option<Vec<Location>>> {
         let uri = params.text_document_position.text_document.uri;
         let position = params.text_document_position.position;
-        if let Some(document) = self.document_map.get(&uri.to_string()) {
-            return Ok(check_goto_references(document.value(), position));
+        let document = self
+            .document_map
+            .get(&uri.to_string())
+            .map(|document| document.value().clone());
+        if let Some(document) = document {
+            let program = self
+                .workspace
+                .get_workspace_program(&uri, &self.document_map);
+            return Ok(check_goto_references(&document, &program, position));
         }
 
         Result::Err(tower_lsp::jsonrpc::Error::new(
@@ -274,6 +570,95 @@ impl LanguageServer for Backend {
         ))
     }
 
+    async fn prepare_rename(
+        &self,
+        params: TextDocumentPositionParams,
+    ) -> Result<Option<PrepareRenameResponse>> {
+        let document = match self.document_map.get(&params.text_document.uri.to_string()) {
+            Some(document) => document,
+            None => return Err(Error {
+                code:ErrorCode::InternalError,
+                message:Cow::Owned("Document not found".to_string()),
+                data:None,
+            }),
+        };
+
+        Ok(rename::prepare(&document, &params))
+    }
+
+    async fn rename(&self, params: RenameParams) -> Result<Option<WorkspaceEdit>> {
+        let uri = &params.text_document_position.text_document.uri;
+        let document = match self.document_map.get(&uri.to_string()) {
+            Some(document) => document.value().clone(),
+            None => return Err(Error {
+                code:ErrorCode::InternalError,
+                message:Cow::Owned("Document not found".to_string()),
+                data:None,
+            }),
+        };
+        // Only the document and its includes are renamed, encodings elsewhere in the workspace may use the name differently
+        let program = self.workspace.get_program(uri, &self.document_map);
+
+        rename::handle(&document, &program, &params).map_err(Error::invalid_params)
+    }
+
+    async fn document_symbol(
+        &self,
+        params: DocumentSymbolParams,
+    ) -> Result<Option<DocumentSymbolResponse>> {
+        let document = match self.document_map.get(&params.text_document.uri.to_string()) {
+            Some(document) => document,
+            None => return Err(Error {
+                code:ErrorCode::InternalError,
+                message:Cow::Owned("Document not found".to_string()),
+                data:None,
+            }),
+        };
+
+        Ok(symbols::document_symbol::handle(&document, &params))
+    }
+
+    async fn symbol(
+        &self,
+        params: WorkspaceSymbolParams,
+    ) -> Result<Option<Vec<SymbolInformation>>> {
+        let program = self.workspace.get_indexed_program(&self.document_map);
+
+        Ok(symbols::workspace_symbol::handle(&program, &params))
+    }
+
+    async fn semantic_tokens_full(
+        &self,
+        params: SemanticTokensParams,
+    ) -> Result<Option<SemanticTokensResult>> {
+        let document = match self.document_map.get(&params.text_document.uri.to_string()) {
+            Some(document) => document,
+            None => return Err(Error {
+                code:ErrorCode::InternalError,
+                message:Cow::Owned("Document not found".to_string()),
+                data:None,
+            }),
+        };
+
+        Ok(semantic_tokens::handle_full(&document, &self.semantic_tokens))
+    }
+
+    async fn semantic_tokens_full_delta(
+        &self,
+        params: SemanticTokensDeltaParams,
+    ) -> Result<Option<SemanticTokensFullDeltaResult>> {
+        let document = match self.document_map.get(&params.text_document.uri.to_string()) {
+            Some(document) => document,
+            None => return Err(Error {
+                code:ErrorCode::InternalError,
+                message:Cow::Owned("Document not found".to_string()),
+                data:None,
+            }),
+        };
+
+        Ok(semantic_tokens::handle_delta(&document, &params, &self.semantic_tokens))
+    }
+
     async fn signature_help(&self, params: SignatureHelpParams)-> Result<Option<SignatureHelp>> {
         let uri = &params.text_document_position_params.text_document.uri;
         let document = match self.document_map.get(&uri.to_string()) {
@@ -301,6 +686,126 @@ impl LanguageServer for Backend {
         
         Ok(hover::handle(&document, &params))
     }
+
+    async fn code_action(&self, params: CodeActionParams) -> Result<Option<CodeActionResponse>> {
+        let document = match self.document_map.get(&params.text_document.uri.to_string()) {
+            Some(document) => document.value().clone(),
+            None => return Err(Error {
+                code:ErrorCode::InternalError,
+                message: Cow::Owned("Document not found".to_string()),
+                data:None,
+            }),
+        };
+
+        let program = self
+            .workspace
+            .get_program(&params.text_document.uri, &self.document_map);
+
+        Ok(code_actions::handle(&document, &program, &params))
+    }
+
+    async fn formatting(&self, params: DocumentFormattingParams) -> Result<Option<Vec<TextEdit>>> {
+        let document = match self.document_map.get(&params.text_document.uri.to_string()) {
+            Some(document) => document,
+            None => return Err(Error {
+                code:ErrorCode::InternalError,
+                message: Cow::Owned("Document not found".to_string()),
+                data:None,
+            }),
+        };
+
+        Ok(formatting::handle(&document, &params))
+    }
+
+    async fn range_formatting(
+        &self,
+        params: DocumentRangeFormattingParams,
+    ) -> Result<Option<Vec<TextEdit>>> {
+        let document = match self.document_map.get(&params.text_document.uri.to_string()) {
+            Some(document) => document,
+            None => return Err(Error {
+                code:ErrorCode::InternalError,
+                message: Cow::Owned("Document not found".to_string()),
+                data:None,
+            }),
+        };
+
+        Ok(formatting::handle_range(&document, &params))
+    }
+
+    async fn on_type_formatting(
+        &self,
+        params: DocumentOnTypeFormattingParams,
+    ) -> Result<Option<Vec<TextEdit>>> {
+        let uri = &params.text_document_position.text_document.uri;
+        let document = match self.document_map.get(&uri.to_string()) {
+            Some(document) => document,
+            None => return Err(Error {
+                code:ErrorCode::InternalError,
+                message: Cow::Owned("Document not found".to_string()),
+                data:None,
+            }),
+        };
+
+        Ok(formatting::on_type::handle(&document, &params))
+    }
+
+    async fn folding_range(&self, params: FoldingRangeParams) -> Result<Option<Vec<FoldingRange>>> {
+        let document = match self.document_map.get(&params.text_document.uri.to_string()) {
+            Some(document) => document,
+            None => return Err(Error {
+                code:ErrorCode::InternalError,
+                message: Cow::Owned("Document not found".to_string()),
+                data:None,
+            }),
+        };
+
+        Ok(folding_range::handle(&document, &params))
+    }
+
+    async fn prepare_call_hierarchy(
+        &self,
+        params: CallHierarchyPrepareParams,
+    ) -> Result<Option<Vec<CallHierarchyItem>>> {
+        let uri = &params.text_document_position_params.text_document.uri;
+        let document = match self.document_map.get(&uri.to_string()) {
+            Some(document) => document.value().clone(),
+            None => return Err(Error {
+                code:ErrorCode::InternalError,
+                message: Cow::Owned("Document not found".to_string()),
+                data:None,
+            }),
+        };
+        let program = self.workspace.get_workspace_program(uri, &self.document_map);
+
+        Ok(call_hierarchy::prepare(&document, &program, &params))
+    }
+
+    async fn incoming_calls(
+        &self,
+        params: CallHierarchyIncomingCallsParams,
+    ) -> Result<Option<Vec<CallHierarchyIncomingCall>>> {
+        let program = self
+            .workspace
+            .get_workspace_program(&params.item.uri, &self.document_map);
+
+        Ok(call_hierarchy::incoming(&program, &params))
+    }
+
+    async fn outgoing_calls(
+        &self,
+        params: CallHierarchyOutgoingCallsParams,
+    ) -> Result<Option<Vec<CallHierarchyOutgoingCall>>> {
+        let program = self
+            .workspace
+            .get_workspace_program(&params.item.uri, &self.document_map);
+
+        Ok(call_hierarchy::outgoing(&program, &params))
+    }
+
+    async fn execute_command(&self, params: ExecuteCommandParams) -> Result<Option<serde_json::Value>> {
+        commands::handle(&self.workspace, &self.document_map, &params).map_err(Error::invalid_params)
+    }
 }
 #[derive(Debug, Deserialize, Serialize)]
 struct InlayHintParams {
@@ -317,12 +822,21 @@ impl Notification for CustomNotification {
 async fn main() {
     env_logger::init();
 
+    let arguments: Vec<String> = std::env::args().collect();
+    if arguments.get(1).map(String::as_str) == Some("check") {
+        std::process::exit(cli::run_check(&arguments[2..]));
+    }
+
     let stdin = tokio::io::stdin();
     let stdout = tokio::io::stdout();
 
     let (service, socket) = LspService::build(|client| Backend {
         client: client.clone(),
         document_map: DashMap::new(),
+        workspace: Arc::new(Workspace::new()),
+        semantic_tokens: DashMap::new(),
+        settings: RwLock::new(Settings::default()),
+        configuration_capability: AtomicBool::new(false),
     })
     .finish();
     Server::new(stdin, stdout, socket).serve(service).await;
@@ -0,0 +1,66 @@
+use tower_lsp::lsp_types::{
+    PrepareRenameResponse, RenameParams, TextDocumentPositionParams, WorkspaceEdit,
+};
+
+use crate::{document::DocumentData, utils::range::to_lsp_range, workspace::program::Program};
+
+pub mod predicate;
+pub mod variable;
+
+/**
+ * Check if the symbol at the position can be renamed and return the range that will be renamed
+ */
+pub fn prepare(
+    document: &DocumentData,
+    params: &TextDocumentPositionParams,
+) -> Option<PrepareRenameResponse> {
+    if let Some((identifier, _, range)) =
+        predicate::get_predicate_at_position(document, params.position)
+    {
+        return Some(PrepareRenameResponse::RangeWithPlaceholder {
+            range: to_lsp_range(range),
+            placeholder: identifier,
+        });
+    }
+
+    let (variable, _) = variable::get_variable_at_position(document, params.position)?;
+
+    Some(PrepareRenameResponse::RangeWithPlaceholder {
+        range: to_lsp_range(variable.range()),
+        placeholder: document.get_source_for_range(variable.range()),
+    })
+}
+
+/**
+ * Rename the symbol at the position in the whole program.
+ * If the new name can not be used an error message is returned
+ */
+pub fn handle(
+    document: &DocumentData,
+    program: &Program,
+    params: &RenameParams,
+) -> Result<Option<WorkspaceEdit>, String> {
+    let position = params.text_document_position.position;
+    let new_name = params.new_name.trim();
+
+    if let Some((identifier, arity, _)) = predicate::get_predicate_at_position(document, position) {
+        if !predicate::is_valid_identifier(new_name) {
+            return Err(format!("'{}' is not a valid predicate name", new_name));
+        }
+
+        return Ok(Some(predicate::rename_predicate(
+            program,
+            &identifier,
+            arity,
+            new_name,
+        )));
+    }
+
+    if let Some((variable, statement)) = variable::get_variable_at_position(document, position) {
+        let name = document.get_source_for_range(variable.range());
+
+        return variable::rename_variable(document, &statement, &name, new_name).map(Some);
+    }
+
+    Ok(None)
+}
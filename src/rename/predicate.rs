@@ -0,0 +1,265 @@
+use std::collections::HashMap;
+
+use tower_lsp::lsp_types::{Position, TextEdit, Url, WorkspaceEdit};
+use tree_sitter::{Node, Point, Query, QueryCursor, Range};
+
+use crate::{
+    document::DocumentData,
+    documentation::Documentation,
+    utils::{node::from_position, range::to_lsp_range},
+    workspace::program::Program,
+};
+
+#[cfg(test)]
+use crate::test_utils::create_test_document;
+#[cfg(test)]
+use std::str::FromStr;
+
+/**
+ * Check if a node is used as a predicate, this is an atom or a term that is shown with #show t : body.
+ * Terms inside of the arguments of an atom are function symbols and not predicates
+ */
+pub fn is_predicate_node(node: &Node) -> bool {
+    if node.child_count() == 0 || node.child(0).unwrap().kind() != "identifier" {
+        return false;
+    }
+
+    match node.kind() {
+        "atom" => true,
+        "term" => node
+            .parent()
+            .is_some_and(|parent| parent.kind() == "statement"),
+        _ => false,
+    }
+}
+
+/**
+ * Find the predicate at a position, returns the identifier, the arity and the range of the identifier
+ */
+pub fn get_predicate_at_position(
+    document: &DocumentData,
+    position: Position,
+) -> Option<(String, usize, Range)> {
+    let node = from_position(document, position)?;
+    if node.kind() != "identifier" {
+        return None;
+    }
+
+    let parent = node.parent()?;
+    let identifier = document.get_source_for_range(node.range());
+
+    if is_predicate_node(&parent) && parent.child(0)? == node {
+        let arity = document
+            .semantics
+            .predicate_semantics
+            .get_arity_for_predicate_node(&parent);
+        return Some((identifier, arity, node.range()));
+    }
+
+    // The identifier could also be part of a signature in a #show statement
+    for refmulti in document
+        .semantics
+        .predicate_semantics
+        .show_signatures
+        .iter()
+    {
+        if refmulti
+            .value()
+            .iter()
+            .any(|signature| signature.identifier_range == node.range())
+        {
+            return Some((refmulti.key().0.clone(), refmulti.key().1, node.range()));
+        }
+    }
+
+    None
+}
+
+/**
+ * Create the edits to rename every occurence of a predicate with this identifier and arity in the program.
+ * Predicates with the same name but a different arity are left untouched
+ */
+pub fn rename_predicate(
+    program: &Program,
+    identifier: &str,
+    arity: usize,
+    new_name: &str,
+) -> WorkspaceEdit {
+    let key = (identifier.to_string(), arity);
+    let mut ranges: Vec<(Url, Range)> = Vec::new();
+
+    // Atoms in the encodings
+    if let Some(occurences) = program.predicate_semantics.predicates.get(&key) {
+        for occurence in occurences.iter() {
+            let identifier_range = program
+                .get_document(&occurence.uri)
+                .and_then(|document| get_identifier_range(document, occurence.range));
+
+            if let Some(range) = identifier_range {
+                ranges.push((occurence.uri.clone(), range));
+            }
+        }
+    }
+
+    // Signatures in #show statements
+    if let Some(signatures) = program.predicate_semantics.show_signatures.get(&key) {
+        for signature in signatures.iter() {
+            ranges.push((signature.uri.clone(), signature.identifier_range));
+        }
+    }
+
+    // Doc comments written for this predicate
+    for document in &program.documents {
+        for range in get_doc_comment_ranges(document, identifier, arity) {
+            ranges.push((document.uri.clone(), range));
+        }
+    }
+
+    let mut changes: HashMap<Url, Vec<TextEdit>> = HashMap::new();
+    for (uri, range) in ranges {
+        let edits = changes.entry(uri).or_default();
+        let edit = TextEdit::new(to_lsp_range(range), new_name.to_string());
+
+        if !edits.contains(&edit) {
+            edits.push(edit);
+        }
+    }
+
+    WorkspaceEdit::new(changes)
+}
+
+/**
+ * Check if a name can be used as the identifier of a predicate
+ */
+pub fn is_valid_identifier(name: &str) -> bool {
+    let trimmed = name.trim_start_matches('_');
+    let mut characters = trimmed.chars();
+
+    match characters.next() {
+        Some(first) if first.is_ascii_lowercase() || first == '\'' => {
+            characters.all(|c| c.is_ascii_alphanumeric() || c == '_' || c == '\'')
+        }
+        _ => false,
+    }
+}
+
+/**
+ * Find the range of the identifier of the predicate occuring at a range
+ */
+pub fn get_identifier_range(document: &DocumentData, range: Range) -> Option<Range> {
+    let mut node = document
+        .tree
+        .root_node()
+        .descendant_for_byte_range(range.start_byte, range.end_byte)?;
+
+    // Multiple nodes can span the same range, e.g. an atom only consisting of an identifier
+    while node.start_byte() == range.start_byte && node.end_byte() == range.end_byte {
+        if is_predicate_node(&node) {
+            return Some(node.child(0)?.range());
+        }
+        node = node.parent()?;
+    }
+
+    None
+}
+
+/**
+ * Find the ranges of the identifiers in doc comments that document a specific predicate
+ */
+fn get_doc_comment_ranges(document: &DocumentData, identifier: &str, arity: usize) -> Vec<Range> {
+    let mut query_cursor = QueryCursor::new();
+    let query = Query::new(tree_sitter_clingo::language(), "(multi_comment) @comment").unwrap();
+    let source = document.get_bytes();
+
+    let mut ranges = Vec::new();
+    for each_match in query_cursor.matches(&query, document.tree.root_node(), source.as_slice()) {
+        for capture in each_match.captures.iter() {
+            let comment = document.get_source_for_range(capture.node.range());
+
+            if let Some((documented_identifier, documented_arity, offset)) =
+                Documentation::get_documented_predicate(&comment)
+            {
+                if documented_identifier == identifier && documented_arity == arity {
+                    let start_byte = capture.node.start_byte() + offset;
+                    let end_byte = start_byte + identifier.len();
+
+                    ranges.push(Range {
+                        start_byte,
+                        end_byte,
+                        start_point: byte_to_point(document, start_byte),
+                        end_point: byte_to_point(document, end_byte),
+                    });
+                }
+            }
+        }
+    }
+
+    ranges
+}
+
+/**
+ * Convert a byte offset in the document into a point
+ */
+fn byte_to_point(document: &DocumentData, byte: usize) -> Point {
+    let row = document.source.byte_to_line(byte);
+
+    Point {
+        row,
+        column: byte - document.source.line_to_byte(row),
+    }
+}
+
+#[test]
+fn predicate_should_be_found_at_position() {
+    let doc = create_test_document("a :- schedule(1,2).\n#show schedule/2.".to_string());
+
+    let (identifier, arity, _) = get_predicate_at_position(&doc, Position::new(0, 6)).unwrap();
+    assert_eq!((identifier.as_str(), arity), ("schedule", 2));
+
+    let (identifier, arity, _) = get_predicate_at_position(&doc, Position::new(1, 7)).unwrap();
+    assert_eq!((identifier.as_str(), arity), ("schedule", 2));
+
+    assert!(get_predicate_at_position(&doc, Position::new(0, 14)).is_none());
+}
+
+#[test]
+fn function_symbols_should_not_be_predicates() {
+    let doc = create_test_document("a(f(1)).".to_string());
+
+    assert!(get_predicate_at_position(&doc, Position::new(0, 2)).is_none());
+}
+
+#[test]
+fn only_predicates_with_the_same_arity_should_be_renamed() {
+    let doc = create_test_document(
+        "%*\n#schedule(A,B).\nA schedule\n*%\nschedule(1,2).\nschedule(1,2,3).\nschedule.\nb(schedule) :- schedule(X,Y), c(X,Y).\n#show schedule/2.\n#show schedule/3."
+            .to_string(),
+    );
+    let program = Program::new(vec![doc.clone()]);
+
+    let edit = rename_predicate(&program, "schedule", 2, "plan");
+    let edits = edit
+        .changes
+        .unwrap()
+        .get(&Url::from_str("file://test.lp").unwrap())
+        .unwrap()
+        .clone();
+
+    let mut lines: Vec<u32> = edits.iter().map(|edit| edit.range.start.line).collect();
+    lines.sort();
+
+    assert_eq!(lines, vec![1, 4, 7, 8]);
+    assert!(edits.iter().all(|edit| edit.new_text == "plan"));
+    assert!(edits.iter().any(
+        |edit| edit.range.start == Position::new(1, 1) && edit.range.end == Position::new(1, 9)
+    ));
+}
+
+#[test]
+fn predicate_identifiers_should_be_validated() {
+    assert!(is_valid_identifier("plan"));
+    assert!(is_valid_identifier("_plan_2"));
+    assert!(!is_valid_identifier("Plan"));
+    assert!(!is_valid_identifier("plan(X)"));
+    assert!(!is_valid_identifier(""));
+}
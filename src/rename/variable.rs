@@ -0,0 +1,112 @@
+use std::collections::HashMap;
+
+use tower_lsp::lsp_types::{Position, TextEdit, WorkspaceEdit};
+use tree_sitter::Node;
+
+use crate::{
+    diagnostics::statement_analysis::get_variables_in_statement,
+    document::DocumentData,
+    utils::{node::from_position, range::to_lsp_range},
+};
+
+#[cfg(test)]
+use crate::test_utils::create_test_document;
+
+/**
+ * Find the variable at a position, returns the variable node and the statement it is part of
+ */
+pub fn get_variable_at_position(
+    document: &DocumentData,
+    position: Position,
+) -> Option<(Node<'_>, Node<'_>)> {
+    let node = from_position(document, position)?;
+    if node.kind() != "VARIABLE" {
+        return None;
+    }
+
+    // Variables are only valid within the statement they occur in
+    let mut statement = node.parent()?;
+    while statement.kind() != "statement" {
+        statement = statement.parent()?;
+    }
+
+    Some((node, statement))
+}
+
+/**
+ * Create the edits to rename every occurence of a variable in a statement.
+ * If the new name is not a valid variable or is already used in the statement an error message is returned
+ */
+pub fn rename_variable(
+    document: &DocumentData,
+    statement: &Node,
+    name: &str,
+    new_name: &str,
+) -> Result<WorkspaceEdit, String> {
+    if !is_valid_variable(new_name) {
+        return Err(format!("'{}' is not a valid variable name", new_name));
+    }
+
+    let vars = document
+        .semantics
+        .get_statement_semantics_for_node(statement.id())
+        .vars;
+    if new_name != name && vars.contains(new_name) {
+        return Err(format!("'{}' is already used in this statement", new_name));
+    }
+
+    let source = document.get_bytes();
+    let edits = get_variables_in_statement(statement, &source)
+        .into_iter()
+        .filter(|(_, var, _)| *var == name)
+        .map(|(range, _, _)| TextEdit::new(to_lsp_range(range), new_name.to_string()))
+        .collect();
+
+    Ok(WorkspaceEdit::new(HashMap::from([(
+        document.uri.clone(),
+        edits,
+    )])))
+}
+
+/**
+ * Check if a name can be used as a variable
+ */
+pub fn is_valid_variable(name: &str) -> bool {
+    let trimmed = name.trim_start_matches('_');
+    let mut characters = trimmed.chars();
+
+    match characters.next() {
+        Some(first) if first.is_ascii_uppercase() => {
+            characters.all(|c| c.is_ascii_alphanumeric() || c == '_' || c == '\'')
+        }
+        _ => false,
+    }
+}
+
+#[test]
+fn variables_should_only_be_renamed_in_their_statement() {
+    let doc = create_test_document("a(X) :- b(X), c(X,Y).\nd(X) :- e(X).".to_string());
+
+    let (variable, statement) = get_variable_at_position(&doc, Position::new(0, 2)).unwrap();
+    let name = doc.get_source_for_range(variable.range());
+    let edit = rename_variable(&doc, &statement, &name, "Agent").unwrap();
+
+    let edits = edit.changes.unwrap().get(&doc.uri).unwrap().clone();
+
+    assert_eq!(edits.len(), 3);
+    assert!(edits
+        .iter()
+        .all(|edit| edit.range.start.line == 0 && edit.new_text == "Agent"));
+}
+
+#[test]
+fn variables_should_not_be_renamed_to_existing_variables() {
+    let doc = create_test_document("a(X) :- b(X), c(X,Y).".to_string());
+
+    let (variable, statement) = get_variable_at_position(&doc, Position::new(0, 2)).unwrap();
+    let name = doc.get_source_for_range(variable.range());
+
+    assert!(rename_variable(&doc, &statement, &name, "Y").is_err());
+    assert!(rename_variable(&doc, &statement, &name, "agent").is_err());
+    assert!(rename_variable(&doc, &statement, &name, "_Agent").is_ok());
+}
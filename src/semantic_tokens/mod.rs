@@ -0,0 +1,494 @@
+use std::{
+    collections::{HashMap, HashSet},
+    sync::atomic::{AtomicUsize, Ordering},
+};
+
+use dashmap::DashMap;
+use tower_lsp::lsp_types::{
+    SemanticToken, SemanticTokenModifier, SemanticTokenType, SemanticTokens, SemanticTokensDelta,
+    SemanticTokensDeltaParams, SemanticTokensEdit, SemanticTokensFullDeltaResult,
+    SemanticTokensLegend, SemanticTokensResult,
+};
+use tree_sitter::{Node, Point};
+
+use crate::{
+    diagnostics::statement_analysis::get_unsafe_variables, document::DocumentData,
+    rename::predicate::get_identifier_range,
+    semantics::predicate_occurence_semantics::PredicateOccurenceLocation,
+};
+
+#[cfg(test)]
+use crate::test_utils::create_test_document;
+
+/**
+ * The token types in the order of the legend, the index in this list is what is sent to the client
+ */
+pub const TOKEN_TYPES: &[SemanticTokenType] = &[
+    SemanticTokenType::FUNCTION,
+    SemanticTokenType::VARIABLE,
+    SemanticTokenType::MACRO,
+    SemanticTokenType::KEYWORD,
+    SemanticTokenType::COMMENT,
+];
+
+/**
+ * The token modifiers in the order of the legend, each modifier is one bit in the bitset sent to the client
+ */
+pub const TOKEN_MODIFIERS: &[SemanticTokenModifier] = &[
+    SemanticTokenModifier::DEFINITION,
+    SemanticTokenModifier::READONLY,
+    SemanticTokenModifier::DOCUMENTATION,
+    SemanticTokenModifier::DEFAULT_LIBRARY,
+    SemanticTokenModifier::new("head"),
+    SemanticTokenModifier::new("body"),
+    SemanticTokenModifier::new("condition"),
+    SemanticTokenModifier::new("anonymous"),
+    SemanticTokenModifier::new("unsafe"),
+];
+
+const PREDICATE: u32 = 0;
+const VARIABLE: u32 = 1;
+const CONSTANT: u32 = 2;
+const DIRECTIVE: u32 = 3;
+const COMMENT: u32 = 4;
+
+const DEFINITION: u32 = 1 << 0;
+const READONLY: u32 = 1 << 1;
+const DOCUMENTATION: u32 = 1 << 2;
+const DEFAULT_LIBRARY: u32 = 1 << 3;
+const HEAD: u32 = 1 << 4;
+const BODY: u32 = 1 << 5;
+const CONDITION: u32 = 1 << 6;
+const ANONYMOUS: u32 = 1 << 7;
+const UNSAFE: u32 = 1 << 8;
+
+const DIRECTIVES: &[&str] = &[
+    "BLOCK",
+    "SHOW",
+    "CONST",
+    "INCLUDE",
+    "EXTERNAL",
+    "MINIMIZE",
+    "MAXIMIZE",
+    "HEURISTIC",
+    "PROJECT",
+    "DEFINED",
+    "EDGE",
+    "THEORY",
+    "SCRIPT",
+];
+
+const AGGREGATE_FUNCTIONS: &[&str] = &["SUM", "SUMP", "MIN", "MAX", "COUNT"];
+
+static NEXT_RESULT_ID: AtomicUsize = AtomicUsize::new(1);
+
+/**
+ * A token before it is encoded relative to the previous token
+ */
+struct AbsoluteToken {
+    start: Point,
+    length: usize,
+    token_type: u32,
+    modifiers: u32,
+}
+
+/**
+ * Everything that is known about the document before walking through the tree
+ */
+struct TokenContext {
+    predicates: HashMap<usize, u32>,
+    constants: HashSet<String>,
+}
+
+/**
+ * The legend tells the client which token types and modifiers the numbers in the tokens stand for
+ */
+pub fn get_legend() -> SemanticTokensLegend {
+    SemanticTokensLegend {
+        token_types: TOKEN_TYPES.to_vec(),
+        token_modifiers: TOKEN_MODIFIERS.to_vec(),
+    }
+}
+
+/**
+ * Compute all semantic tokens of a document and remember them for later delta requests
+ */
+pub fn handle_full(
+    document: &DocumentData,
+    previous_tokens: &DashMap<String, SemanticTokens>,
+) -> Option<SemanticTokensResult> {
+    let tokens = create_semantic_tokens(document);
+    previous_tokens.insert(document.uri.to_string(), tokens.clone());
+
+    Some(SemanticTokensResult::Tokens(tokens))
+}
+
+/**
+ * Compute the semantic tokens of a document and only send the part that changed since the previous request.
+ * If the previous tokens are not known anymore all tokens are sent
+ */
+pub fn handle_delta(
+    document: &DocumentData,
+    params: &SemanticTokensDeltaParams,
+    previous_tokens: &DashMap<String, SemanticTokens>,
+) -> Option<SemanticTokensFullDeltaResult> {
+    let tokens = create_semantic_tokens(document);
+    let previous = previous_tokens.insert(document.uri.to_string(), tokens.clone());
+
+    match previous {
+        Some(previous) if previous.result_id.as_ref() == Some(&params.previous_result_id) => Some(
+            SemanticTokensFullDeltaResult::TokensDelta(SemanticTokensDelta {
+                result_id: tokens.result_id,
+                edits: get_edits(&previous.data, &tokens.data),
+            }),
+        ),
+        _ => Some(SemanticTokensFullDeltaResult::Tokens(tokens)),
+    }
+}
+
+/**
+ * Create the semantic tokens of a document with a new result id
+ */
+pub fn create_semantic_tokens(document: &DocumentData) -> SemanticTokens {
+    let context = TokenContext {
+        predicates: get_predicate_modifiers(document),
+        constants: get_constants(document),
+    };
+
+    let mut tokens = Vec::new();
+    collect_tokens(
+        &document.tree.root_node(),
+        document,
+        &context,
+        &HashSet::new(),
+        &mut tokens,
+    );
+
+    SemanticTokens {
+        result_id: Some(NEXT_RESULT_ID.fetch_add(1, Ordering::Relaxed).to_string()),
+        data: encode_tokens(tokens),
+    }
+}
+
+/**
+ * Find the identifiers of all predicates in the document and the modifiers for the place they occur in
+ */
+fn get_predicate_modifiers(document: &DocumentData) -> HashMap<usize, u32> {
+    let mut predicates = HashMap::new();
+
+    for refmulti in document.semantics.predicate_semantics.predicates.iter() {
+        for occurence in refmulti.value().iter() {
+            let range = match get_identifier_range(document, occurence.range) {
+                Some(range) => range,
+                None => continue,
+            };
+
+            let modifiers = match occurence.location {
+                PredicateOccurenceLocation::Head => HEAD | DEFINITION,
+                PredicateOccurenceLocation::Body => BODY,
+                PredicateOccurenceLocation::Condition => CONDITION,
+            };
+            predicates.insert(range.start_byte, modifiers);
+        }
+    }
+
+    for refmulti in document
+        .semantics
+        .predicate_semantics
+        .show_signatures
+        .iter()
+    {
+        for signature in refmulti.value().iter() {
+            predicates.insert(signature.identifier_range.start_byte, 0);
+        }
+    }
+
+    predicates
+}
+
+/**
+ * Find the names of all constants defined with #const in the document
+ */
+fn get_constants(document: &DocumentData) -> HashSet<String> {
+    let root = document.tree.root_node();
+
+    root.children(&mut root.walk())
+        .filter(|statement| statement.child(0).map(|child| child.kind()) == Some("CONST"))
+        .filter_map(|statement| statement.child(1))
+        .filter(|identifier| identifier.kind() == "identifier")
+        .map(|identifier| document.get_source_for_range(identifier.range()))
+        .collect()
+}
+
+/**
+ * Walk through the tree and collect the tokens, the unsafe variables are those of the statement the node is in
+ */
+fn collect_tokens(
+    node: &Node,
+    document: &DocumentData,
+    context: &TokenContext,
+    unsafe_variables: &HashSet<String>,
+    tokens: &mut Vec<AbsoluteToken>,
+) {
+    let kind = node.kind();
+
+    if DIRECTIVES.contains(&kind) {
+        tokens.push(create_token(node, DIRECTIVE, 0));
+        return;
+    }
+
+    if AGGREGATE_FUNCTIONS.contains(&kind) {
+        tokens.push(create_token(node, PREDICATE, DEFAULT_LIBRARY));
+        return;
+    }
+
+    match kind {
+        "statement" => {
+            let unsafe_variables = get_unsafe_variables(node, document);
+
+            for child in node.children(&mut node.walk()) {
+                collect_tokens(&child, document, context, &unsafe_variables, tokens);
+            }
+        }
+        "identifier" => {
+            if let Some(modifiers) = context.predicates.get(&node.start_byte()) {
+                tokens.push(create_token(node, PREDICATE, *modifiers));
+            } else if is_constant(node, document, context) {
+                let modifiers = match node.parent() {
+                    Some(parent) if parent.kind() == "statement" => READONLY | DEFINITION,
+                    _ => READONLY,
+                };
+                tokens.push(create_token(node, CONSTANT, modifiers));
+            }
+        }
+        "VARIABLE" => {
+            let name = document.get_source_for_range(node.range());
+            let modifiers = if unsafe_variables.contains(&name) {
+                UNSAFE
+            } else {
+                0
+            };
+            tokens.push(create_token(node, VARIABLE, modifiers));
+        }
+        "ANONYMOUS" => tokens.push(create_token(node, VARIABLE, ANONYMOUS)),
+        "multi_comment" => {
+            let comment = document.get_source_for_range(node.range());
+            if comment.len() > 4 && comment[2..comment.len() - 2].trim().starts_with('#') {
+                tokens.extend(create_comment_tokens(node, &comment));
+            }
+        }
+        _ => {
+            for child in node.children(&mut node.walk()) {
+                collect_tokens(&child, document, context, unsafe_variables, tokens);
+            }
+        }
+    }
+}
+
+/**
+ * Check if an identifier is the name of a constant, either in its #const definition or where it is used
+ */
+fn is_constant(node: &Node, document: &DocumentData, context: &TokenContext) -> bool {
+    let parent = match node.parent() {
+        Some(parent) => parent,
+        None => return false,
+    };
+
+    let is_definition =
+        parent.kind() == "statement" && parent.child(0).map(|child| child.kind()) == Some("CONST");
+    let is_usage = parent.kind() == "term" && parent.child_count() == 1;
+
+    (is_definition || is_usage)
+        && context
+            .constants
+            .contains(&document.get_source_for_range(node.range()))
+}
+
+fn create_token(node: &Node, token_type: u32, modifiers: u32) -> AbsoluteToken {
+    AbsoluteToken {
+        start: node.start_position(),
+        length: node.end_byte() - node.start_byte(),
+        token_type,
+        modifiers,
+    }
+}
+
+/**
+ * Doc comments can span multiple lines, but tokens can not, so there is one token for every line of the comment
+ */
+fn create_comment_tokens(node: &Node, comment: &str) -> Vec<AbsoluteToken> {
+    let start = node.start_position();
+
+    comment
+        .split('\n')
+        .enumerate()
+        .filter(|(_, line)| !line.trim_end_matches('\r').is_empty())
+        .map(|(index, line)| AbsoluteToken {
+            start: Point {
+                row: start.row + index,
+                column: if index == 0 { start.column } else { 0 },
+            },
+            length: line.trim_end_matches('\r').len(),
+            token_type: COMMENT,
+            modifiers: DOCUMENTATION,
+        })
+        .collect()
+}
+
+/**
+ * Sort the tokens and encode each of them relative to the token before it
+ */
+fn encode_tokens(mut tokens: Vec<AbsoluteToken>) -> Vec<SemanticToken> {
+    tokens.sort_by_key(|token| (token.start.row, token.start.column));
+
+    let mut encoded = Vec::with_capacity(tokens.len());
+    let mut previous = Point { row: 0, column: 0 };
+    let mut previous_end = 0;
+
+    for token in tokens {
+        // Tokens are not allowed to overlap
+        if !encoded.is_empty()
+            && token.start.row == previous.row
+            && token.start.column < previous_end
+        {
+            continue;
+        }
+
+        let delta_line = token.start.row - previous.row;
+        let delta_start = if delta_line == 0 {
+            token.start.column - previous.column
+        } else {
+            token.start.column
+        };
+
+        encoded.push(SemanticToken {
+            delta_line: delta_line as u32,
+            delta_start: delta_start as u32,
+            length: token.length as u32,
+            token_type: token.token_type,
+            token_modifiers_bitset: token.modifiers,
+        });
+
+        previous = token.start;
+        previous_end = token.start.column + token.length;
+    }
+
+    encoded
+}
+
+/**
+ * Find the part of the tokens that changed, everything before and after it stays the same
+ */
+fn get_edits(previous: &[SemanticToken], current: &[SemanticToken]) -> Vec<SemanticTokensEdit> {
+    let prefix = previous
+        .iter()
+        .zip(current.iter())
+        .take_while(|(a, b)| a == b)
+        .count();
+
+    let suffix = previous[prefix..]
+        .iter()
+        .rev()
+        .zip(current[prefix..].iter().rev())
+        .take_while(|(a, b)| a == b)
+        .count();
+
+    let deleted = previous.len() - prefix - suffix;
+    let inserted = &current[prefix..current.len() - suffix];
+
+    if deleted == 0 && inserted.is_empty() {
+        return Vec::new();
+    }
+
+    // Each token consists of five numbers, the edits work on those numbers
+    vec![SemanticTokensEdit {
+        start: (prefix * 5) as u32,
+        delete_count: (deleted * 5) as u32,
+        data: Some(inserted.to_vec()),
+    }]
+}
+
+#[cfg(test)]
+fn get_test_tokens(source: &str) -> Vec<(u32, u32, u32, u32, u32)> {
+    let document = create_test_document(source.to_string());
+    let mut line = 0;
+    let mut column = 0;
+
+    create_semantic_tokens(&document)
+        .data
+        .iter()
+        .map(|token| {
+            if token.delta_line > 0 {
+                column = 0;
+            }
+            line += token.delta_line;
+            column += token.delta_start;
+
+            (
+                line,
+                column,
+                token.length,
+                token.token_type,
+                token.token_modifiers_bitset,
+            )
+        })
+        .collect()
+}
+
+#[test]
+fn predicates_should_be_distinguished_by_occurence() {
+    let tokens = get_test_tokens("{ a(X) : e(X) } :- b(X).");
+
+    assert_eq!(
+        tokens,
+        vec![
+            (0, 2, 1, PREDICATE, HEAD | DEFINITION),
+            (0, 4, 1, VARIABLE, 0),
+            (0, 9, 1, PREDICATE, CONDITION),
+            (0, 11, 1, VARIABLE, 0),
+            (0, 19, 1, PREDICATE, BODY),
+            (0, 21, 1, VARIABLE, 0),
+        ]
+    );
+}
+
+#[test]
+fn constants_directives_and_aggregates_should_be_tokens() {
+    let tokens = get_test_tokens(
+        "%*#a(X).\nThe a*%\n#const n = 2.\nb(X,_) :- #count{ Y : a(Y) } < n, a(Z).",
+    );
+
+    assert_eq!(
+        tokens,
+        vec![
+            (0, 0, 8, COMMENT, DOCUMENTATION),
+            (1, 0, 7, COMMENT, DOCUMENTATION),
+            (2, 0, 6, DIRECTIVE, 0),
+            (2, 7, 1, CONSTANT, READONLY | DEFINITION),
+            (3, 0, 1, PREDICATE, HEAD | DEFINITION),
+            (3, 2, 1, VARIABLE, UNSAFE),
+            (3, 4, 1, VARIABLE, ANONYMOUS),
+            (3, 10, 6, PREDICATE, DEFAULT_LIBRARY),
+            (3, 18, 1, VARIABLE, 0),
+            (3, 22, 1, PREDICATE, BODY),
+            (3, 24, 1, VARIABLE, 0),
+            (3, 31, 1, CONSTANT, READONLY),
+            (3, 34, 1, PREDICATE, BODY),
+            (3, 36, 1, VARIABLE, 0),
+        ]
+    );
+}
+
+#[test]
+fn delta_should_only_contain_changed_tokens() {
+    let previous = create_semantic_tokens(&create_test_document("a. b. c.".to_string()));
+    let current = create_semantic_tokens(&create_test_document("a. b(X) :- d(X). c.".to_string()));
+
+    let edits = get_edits(&previous.data, &current.data);
+
+    // a and b stay the same, c is now relative to the new tokens before it
+    assert_eq!(edits.len(), 1);
+    assert_eq!(edits[0].start, 10);
+    assert_eq!(edits[0].delete_count, 5);
+    assert_eq!(edits[0].data.as_ref().unwrap().len(), 4);
+}
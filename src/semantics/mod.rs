@@ -11,7 +11,7 @@ pub mod encoding_semantic;
 mod error_semantic;
 mod missing_semantic;
 pub mod predicate_occurence_semantics;
-mod predicate_semantics;
+pub mod predicate_semantics;
 pub mod special_literal_semantic;
 mod statement_semantic;
 mod syntax;
@@ -1,8 +1,11 @@
+use tower_lsp::lsp_types::Url;
+
 /**
  * Predicate Occurence Semantics infers information where a predicate occured
  */
 #[derive(Clone, Debug, Eq, Hash, PartialEq)]
 pub struct PredicateOccurenceSemantics {
+    pub uri: Url,
     pub node_id: usize,
     pub range: tree_sitter::Range,
     pub location: PredicateOccurenceLocation,
@@ -17,3 +20,13 @@ pub enum PredicateOccurenceLocation {
     Body,
     Condition,
 }
+
+/**
+ * Signature Semantics contains where a predicate signature like p/2 occured, for example in a #show statement
+ */
+#[derive(Clone, Debug, Eq, Hash, PartialEq)]
+pub struct SignatureSemantics {
+    pub uri: Url,
+    pub range: tree_sitter::Range,
+    pub identifier_range: tree_sitter::Range,
+}
@@ -2,7 +2,9 @@ use std::collections::HashSet;
 
 use super::{
     encoding_semantic::{EncodingSemantics, Semantics},
-    predicate_occurence_semantics::{PredicateOccurenceLocation, PredicateOccurenceSemantics},
+    predicate_occurence_semantics::{
+        PredicateOccurenceLocation, PredicateOccurenceSemantics, SignatureSemantics,
+    },
 };
 use dashmap::DashMap;
 
@@ -13,6 +15,11 @@ use dashmap::DashMap;
 pub struct PredicateSemantics {
     pub predicates: DashMap<(String, usize), HashSet<PredicateOccurenceSemantics>>,
     pub predicates_arity: DashMap<usize, usize>,
+
+    /**
+     * The signatures of predicates that are shown with a #show p/n. statement
+     */
+    pub show_signatures: DashMap<(String, usize), HashSet<SignatureSemantics>>,
 }
 
 impl PredicateSemantics {
@@ -20,6 +27,7 @@ impl PredicateSemantics {
         PredicateSemantics {
             predicates: DashMap::new(),
             predicates_arity: DashMap::new(),
+            show_signatures: DashMap::new(),
         }
     }
 
@@ -54,6 +62,44 @@ impl PredicateSemantics {
             .insert((identifier, arity), hash);
     }
 
+    /**
+     * Combine the predicates of multiple encodings into a single view of the whole program.
+     * The arity lookup is bound to the nodes of a single tree and is therefore not merged
+     */
+    pub fn merge(semantics: Vec<&PredicateSemantics>) -> PredicateSemantics {
+        let merged = PredicateSemantics::new();
+
+        for predicate_semantics in semantics {
+            for refmulti in predicate_semantics.predicates.iter() {
+                merged
+                    .predicates
+                    .entry(refmulti.key().clone())
+                    .or_default()
+                    .extend(refmulti.value().iter().cloned());
+            }
+
+            for refmulti in predicate_semantics.show_signatures.iter() {
+                merged
+                    .show_signatures
+                    .entry(refmulti.key().clone())
+                    .or_default()
+                    .extend(refmulti.value().iter().cloned());
+            }
+        }
+
+        merged
+    }
+
+    /**
+     * Returns the arity of an atom or term that starts with an identifier
+     */
+    pub fn get_arity_for_predicate_node(&self, node: &tree_sitter::Node) -> usize {
+        if node.child_count() < 3 {
+            return 0;
+        }
+        self.get_predicates_arity_for_node(&node.child(2).unwrap().id()) + 1
+    }
+
     /**
      * Returns the amount of termvecs in this part of the encoding
      */
@@ -89,7 +135,9 @@ impl Semantics for PredicateSemantics {
                     while parent.is_some() {
                         match parent.unwrap().kind() {
                             "bodydot" => location = PredicateOccurenceLocation::Body,
-                            "optcondition" => location = PredicateOccurenceLocation::Condition,
+                            "optcondition" | "litvec" | "optimizecond" => {
+                                location = PredicateOccurenceLocation::Condition
+                            }
                             _ => {}
                         }
                         parent = parent.unwrap().parent();
@@ -100,6 +148,7 @@ impl Semantics for PredicateSemantics {
                         identifier,
                         arity,
                         PredicateOccurenceSemantics {
+                            uri: document.uri.clone(),
                             node_id: node.id(),
                             range: node.range(),
                             location,
@@ -122,6 +171,40 @@ impl Semantics for PredicateSemantics {
                     .predicates_arity
                     .insert(node.id(), arity);
             }
+            // A #show statement for a signature consists of: SHOW identifier SLASH NUMBER DOT
+            "statement"
+                if node.child_count() == 5
+                    && node.child(0).unwrap().kind() == "SHOW"
+                    && node.child(1).unwrap().kind() == "identifier"
+                    && node.child(2).unwrap().kind() == "SLASH" =>
+            {
+                let identifier_node = node.child(1).unwrap();
+                let number_node = node.child(3).unwrap();
+                let arity = match document
+                    .get_source_for_range(number_node.range())
+                    .parse::<usize>()
+                {
+                    Ok(arity) => arity,
+                    Err(_) => return,
+                };
+
+                document
+                    .semantics
+                    .predicate_semantics
+                    .show_signatures
+                    .entry((document.get_source_for_range(identifier_node.range()), arity))
+                    .or_default()
+                    .insert(SignatureSemantics {
+                        uri: document.uri.clone(),
+                        range: tree_sitter::Range {
+                            start_byte: identifier_node.start_byte(),
+                            end_byte: number_node.end_byte(),
+                            start_point: identifier_node.start_position(),
+                            end_point: number_node.end_position(),
+                        },
+                        identifier_range: identifier_node.range(),
+                    });
+            }
             "COMMA" => {
                 document
                     .semantics
@@ -136,5 +219,6 @@ impl Semantics for PredicateSemantics {
     fn startup(document: &mut crate::document::DocumentData) {
         document.semantics.predicate_semantics.predicates = DashMap::new();
         document.semantics.predicate_semantics.predicates_arity = DashMap::new();
+        document.semantics.predicate_semantics.show_signatures = DashMap::new();
     }
 }
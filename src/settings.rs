@@ -0,0 +1,108 @@
+use std::path::PathBuf;
+
+use serde::Deserialize;
+use serde_json::Value;
+
+use crate::diagnostics::diagnostic_settings::DiagnosticSettings;
+
+/**
+ * The section of the client configuration the settings are read from with workspace/configuration
+ */
+pub const SECTION: &str = "asp-lsp";
+
+/**
+ * The settings of the language server, e.g. {"maximumNumberOfProblems": 50, "includePaths": ["lib"], "diagnostics": {"codes": {"UnsafeVariable": "warning"}}}
+ */
+#[derive(Clone, Debug, Deserialize, Eq, PartialEq)]
+#[serde(rename_all = "camelCase", default)]
+pub struct Settings {
+    /**
+     * The maximum number of problems reported for a single document
+     */
+    pub maximum_number_of_problems: u32,
+
+    pub diagnostics: DiagnosticSettings,
+
+    /**
+     * Additional folders that are searched for files included with #include
+     */
+    pub include_paths: Vec<PathBuf>,
+}
+
+impl Default for Settings {
+    fn default() -> Self {
+        Settings {
+            maximum_number_of_problems: 100,
+            diagnostics: DiagnosticSettings::default(),
+            include_paths: Vec::new(),
+        }
+    }
+}
+
+impl Settings {
+    /**
+     * Read the settings from the initialization options or a configuration sent by the client.
+     * Clients may send the settings wrapped in their section, settings that are not given keep their default
+     */
+    pub fn from_value(value: &Value) -> Result<Settings, String> {
+        let value = value.get(SECTION).unwrap_or(value);
+        if value.is_null() {
+            return Ok(Settings::default());
+        }
+
+        serde_json::from_value(value.clone()).map_err(|error| error.to_string())
+    }
+}
+
+#[test]
+fn settings_should_be_read_with_defaults() {
+    use crate::diagnostics::diagnostic_settings::DiagnosticLevel;
+    use serde_json::json;
+
+    let settings = Settings::from_value(&json!({
+        "asp-lsp": {
+            "maximumNumberOfProblems": 10,
+            "includePaths": ["lib"],
+            "diagnostics": {"enable": false, "unusedPredicate": "off"}
+        }
+    }))
+    .unwrap();
+
+    assert_eq!(settings.maximum_number_of_problems, 10);
+    assert_eq!(settings.include_paths, vec![PathBuf::from("lib")]);
+    assert!(!settings.diagnostics.enable);
+    assert_eq!(settings.diagnostics.unused_predicate, DiagnosticLevel::Off);
+    assert_eq!(settings.diagnostics.unmatched_show, DiagnosticLevel::Warning);
+
+    assert_eq!(Settings::from_value(&Value::Null).unwrap(), Settings::default());
+    assert!(Settings::from_value(&json!({"maximumNumberOfProblems": "many"})).is_err());
+}
+
+#[test]
+fn settings_contributed_by_the_extension_should_match_the_defaults() {
+    let manifest: Value =
+        serde_json::from_str(include_str!("../editors/vscode/package.json")).unwrap();
+    let properties = manifest["contributes"]["configuration"]["properties"]
+        .as_object()
+        .unwrap();
+
+    // Turn the dotted names like asp-lsp.diagnostics.oddLoop back into nested objects
+    let mut value = serde_json::json!({});
+    for (name, property) in properties {
+        let mut current = &mut value;
+        for part in name.split('.') {
+            current = current
+                .as_object_mut()
+                .unwrap()
+                .entry(part)
+                .or_insert(serde_json::json!({}));
+        }
+        *current = property["default"].clone();
+    }
+
+    let settings: Settings = serde_json::from_value(value[SECTION].clone()).unwrap();
+    assert_eq!(settings, Settings::default());
+
+    // Rules that are off by default can only be found in the settings if they are contributed
+    assert!(properties.contains_key("asp-lsp.diagnostics.arityMismatch"));
+}
@@ -1,31 +1,221 @@
-use log::info;
-use tower_lsp::lsp_types::{SignatureHelp, SignatureHelpParams};
+use tower_lsp::lsp_types::{
+    Documentation, MarkupContent, MarkupKind, ParameterInformation, ParameterLabel, SignatureHelp,
+    SignatureHelpParams, SignatureInformation,
+};
+use tree_sitter::Node;
 
-use crate::{document::DocumentData, documentation, utils};
+use crate::{
+    document::DocumentData,
+    documentation::{self, PredicateDocumentation},
+    utils,
+};
 
-pub fn handle(document:&DocumentData, params: &SignatureHelpParams) -> Option<SignatureHelp> {
+#[cfg(test)]
+use crate::test_utils::create_test_document;
+#[cfg(test)]
+use std::str::FromStr;
+#[cfg(test)]
+use tower_lsp::lsp_types::{Position, TextDocumentIdentifier, TextDocumentPositionParams, Url};
+
+pub fn handle(document: &DocumentData, params: &SignatureHelpParams) -> Option<SignatureHelp> {
     let position = params.text_document_position_params.position;
-    info!("{:?}", position);
-    let target_node = utils::node::from_position(&document, position)?;
+    let target_node = utils::node::from_position(document, position)?;
     let atom_node = utils::node::get_atom(target_node)?;
 
-    let arguments = documentation::Documentation::get_atom_arguments(&atom_node, &document.source.to_string());
-    
-    info!("args {:?}", arguments.is_none());
-    let argument_position = utils::node::get_argument_position(target_node)?;
-    info!("pos {:?}", argument_position);
-    let arity = arguments.unwrap().len();
-
-    None
-
-    /*Ok(Some(SignatureHelp {
-            signatures: vec![SignatureInformation {
-                label: identifier.clone(),
-                documentation: Some(Documentation::String(format!("DOCU: {}", identifier))),
-                parameters: Some(parameters),
-                active_parameter: Some(1),
-            }],
-            active_signature: Some(0),
-            active_parameter: Some(0),
-        })) */
-}
\ No newline at end of file
+    let arguments =
+        documentation::Documentation::get_atom_arguments(&atom_node, &document.source.to_string())?;
+    let arity = arguments.len();
+    let identifier = document.get_source_for_range(atom_node.child(0)?.range());
+
+    // The argument position is counted from the last argument and only works inside of an argument
+    let is_separator = target_node
+        .parent()
+        .is_some_and(|parent| parent.kind() == "termvec");
+    let active_argument = match utils::node::get_argument_position(target_node) {
+        Some(position) if position < arity && !is_separator => Some(arity - 1 - position),
+        _ => get_argument_position_from_commas(&atom_node, target_node),
+    };
+
+    let signature = match document
+        .documentation
+        .predicates
+        .get(&(identifier.clone(), arity))
+    {
+        Some(documentation) => create_documented_signature(&documentation),
+        None => create_generic_signature(&identifier, arity),
+    };
+
+    Some(SignatureHelp {
+        signatures: vec![signature],
+        active_signature: Some(0),
+        active_parameter: active_argument.map(|argument| argument as u32),
+    })
+}
+
+/**
+ * If the cursor is not inside of an argument, e.g. on a comma or a parenthesis,
+ * the active argument is the number of commas in front of the cursor
+ */
+fn get_argument_position_from_commas(atom: &Node, target: Node) -> Option<usize> {
+    let argvec = atom.child(2).filter(|node| node.kind() == "argvec")?;
+
+    let mut commas = 0;
+    let mut nodes = vec![argvec];
+    while let Some(node) = nodes.pop() {
+        if node.kind() == "COMMA" && node.start_byte() < target.end_byte() {
+            commas += 1;
+        }
+
+        // Commas in nested terms do not separate the arguments of this atom
+        if node.kind() != "term" {
+            nodes.extend(node.children(&mut node.walk()));
+        }
+    }
+
+    Some(commas)
+}
+
+/**
+ * Create the signature from the documentation of a predicate, every argument is a parameter
+ */
+fn create_documented_signature(documentation: &PredicateDocumentation) -> SignatureInformation {
+    let label = documentation.signature.trim_end_matches('.').to_string();
+
+    let parameters = get_argument_offsets(&label)
+        .into_iter()
+        .map(|(start, end)| {
+            let name = &label[start..end];
+            let description = documentation
+                .arguments
+                .iter()
+                .find(|argument| argument.identifier == name)
+                .map(|argument| Documentation::String(argument.description.clone()));
+
+            ParameterInformation {
+                label: ParameterLabel::LabelOffsets([start as u32, end as u32]),
+                documentation: description,
+            }
+        })
+        .collect();
+
+    SignatureInformation {
+        label,
+        documentation: Some(Documentation::MarkupContent(MarkupContent {
+            kind: MarkupKind::Markdown,
+            value: documentation.description.clone(),
+        })),
+        parameters: Some(parameters),
+        active_parameter: None,
+    }
+}
+
+/**
+ * Create a signature for a predicate without documentation in the form of p(arg1,...,argN)
+ */
+fn create_generic_signature(identifier: &str, arity: usize) -> SignatureInformation {
+    let arguments: Vec<String> = (1..=arity).map(|index| format!("arg{}", index)).collect();
+
+    let label = if arity > 0 {
+        format!("{}({})", identifier, arguments.join(","))
+    } else {
+        identifier.to_string()
+    };
+
+    let parameters = get_argument_offsets(&label)
+        .into_iter()
+        .map(|(start, end)| ParameterInformation {
+            label: ParameterLabel::LabelOffsets([start as u32, end as u32]),
+            documentation: None,
+        })
+        .collect();
+
+    SignatureInformation {
+        label,
+        documentation: None,
+        parameters: Some(parameters),
+        active_parameter: None,
+    }
+}
+
+/**
+ * Find the start and end of every argument in a signature like p(A,f(B,C)),
+ * commas inside of nested terms do not separate arguments
+ */
+fn get_argument_offsets(signature: &str) -> Vec<(usize, usize)> {
+    let start = match signature.find('(') {
+        Some(index) => index + 1,
+        None => return Vec::new(),
+    };
+    let end = signature.rfind(')').unwrap_or(signature.len());
+
+    let mut offsets = Vec::new();
+    let mut depth = 0;
+    let mut argument_start = start;
+
+    for (index, character) in signature[start..end].char_indices() {
+        match character {
+            '(' => depth += 1,
+            ')' => depth -= 1,
+            ',' if depth == 0 => {
+                offsets.push((argument_start, start + index));
+                argument_start = start + index + 1;
+            }
+            _ => {}
+        }
+    }
+
+    if argument_start < end {
+        offsets.push((argument_start, end));
+    }
+
+    offsets
+}
+
+#[cfg(test)]
+fn get_test_signature_help(source: &str, position: Position) -> Option<SignatureHelp> {
+    let params = SignatureHelpParams {
+        context: None,
+        text_document_position_params: TextDocumentPositionParams {
+            text_document: TextDocumentIdentifier::new(Url::from_str("file://test.lp").unwrap()),
+            position,
+        },
+        work_done_progress_params: Default::default(),
+    };
+
+    handle(&create_test_document(source.to_string()), &params)
+}
+
+#[test]
+fn documented_predicates_should_have_their_signature() {
+    let help = get_test_signature_help(
+        "%*#assign(T,M).\nAssign a task\n#parameters\nT : The task\nM : The machine\n*%\na :- assign(1,m1).",
+        Position::new(6, 14),
+    )
+    .unwrap();
+
+    let signature = &help.signatures[0];
+    assert_eq!(signature.label, "assign(T,M)");
+    assert_eq!(help.active_parameter, Some(1));
+
+    let parameters = signature.parameters.as_ref().unwrap();
+    assert_eq!(parameters.len(), 2);
+    assert_eq!(parameters[0].label, ParameterLabel::LabelOffsets([7, 8]));
+    assert_eq!(
+        parameters[1].documentation,
+        Some(Documentation::String("The machine".to_string()))
+    );
+}
+
+#[test]
+fn undocumented_predicates_should_have_a_generic_signature() {
+    let help = get_test_signature_help("a :- schedule(1,2,3).", Position::new(0, 14)).unwrap();
+
+    assert_eq!(help.signatures[0].label, "schedule(arg1,arg2,arg3)");
+    assert_eq!(help.active_parameter, Some(0));
+
+    let help = get_test_signature_help("a :- schedule(1,2,3).", Position::new(0, 15)).unwrap();
+    assert_eq!(help.active_parameter, Some(1));
+
+    let help = get_test_signature_help("a :- schedule(1,2,3).", Position::new(0, 19)).unwrap();
+    assert_eq!(help.active_parameter, Some(2));
+}
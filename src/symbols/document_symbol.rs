@@ -0,0 +1,318 @@
+use tower_lsp::lsp_types::{
+    DocumentSymbol, DocumentSymbolParams, DocumentSymbolResponse, SymbolKind,
+};
+use tree_sitter::Node;
+
+use crate::{document::DocumentData, utils::range::to_lsp_range};
+
+#[cfg(test)]
+use crate::test_utils::create_test_document;
+#[cfg(test)]
+use std::str::FromStr;
+#[cfg(test)]
+use tower_lsp::lsp_types::{TextDocumentIdentifier, Url};
+
+/**
+ * Create the outline of a document. Each #program part is a container for the statements that follow it
+ */
+pub fn handle(
+    document: &DocumentData,
+    _params: &DocumentSymbolParams,
+) -> Option<DocumentSymbolResponse> {
+    let root = document.tree.root_node();
+    let mut symbols: Vec<DocumentSymbol> = Vec::new();
+    let mut program_part: Option<DocumentSymbol> = None;
+
+    for statement in root.children(&mut root.walk()) {
+        if statement.kind() != "statement" || statement.child_count() == 0 {
+            continue;
+        }
+
+        if statement.child(0).unwrap().kind() == "BLOCK" {
+            symbols.extend(program_part.take());
+            program_part = Some(create_symbol(
+                document
+                    .get_source_for_range(statement.range())
+                    .trim_end_matches('.')
+                    .to_string(),
+                "program part".to_string(),
+                SymbolKind::NAMESPACE,
+                &statement,
+                &statement,
+            ));
+            continue;
+        }
+
+        let symbol = match get_statement_symbol(document, &statement) {
+            Some(symbol) => symbol,
+            None => continue,
+        };
+
+        match program_part.as_mut() {
+            Some(part) => {
+                // The program part spans until the last statement that belongs to it
+                part.range.end = symbol.range.end;
+                part.children.get_or_insert_with(Vec::new).push(symbol);
+            }
+            None => symbols.push(symbol),
+        }
+    }
+    symbols.extend(program_part);
+
+    Some(DocumentSymbolResponse::Nested(symbols))
+}
+
+/**
+ * Create a symbol for a statement based on what kind of statement it is
+ */
+fn get_statement_symbol(document: &DocumentData, statement: &Node) -> Option<DocumentSymbol> {
+    let first = statement.child(0)?;
+
+    match first.kind() {
+        "head" => {
+            let is_fact = !statement
+                .children(&mut statement.walk())
+                .any(|child| child.kind() == "bodydot")
+                && first
+                    .child(0)
+                    .is_some_and(|child| child.kind() == "literal");
+
+            let (name, selection) = match get_head_signatures(document, &first) {
+                (signatures, Some(selection)) => (signatures.join(", "), selection),
+                (_, None) => (document.get_source_for_range(first.range()), first),
+            };
+
+            if is_fact {
+                Some(create_symbol(
+                    name,
+                    "fact".to_string(),
+                    SymbolKind::CONSTANT,
+                    statement,
+                    &selection,
+                ))
+            } else {
+                Some(create_symbol(
+                    name,
+                    "rule".to_string(),
+                    SymbolKind::FUNCTION,
+                    statement,
+                    &selection,
+                ))
+            }
+        }
+        "IF" => Some(create_symbol(
+            ":-".to_string(),
+            "constraint".to_string(),
+            SymbolKind::EVENT,
+            statement,
+            &first,
+        )),
+        "WIF" => Some(create_symbol(
+            ":~".to_string(),
+            "weak constraint".to_string(),
+            SymbolKind::OPERATOR,
+            statement,
+            &first,
+        )),
+        "MINIMIZE" | "MAXIMIZE" => Some(create_symbol(
+            document.get_source_for_range(first.range()),
+            "optimization".to_string(),
+            SymbolKind::OPERATOR,
+            statement,
+            &first,
+        )),
+        "SHOW" => Some(create_symbol(
+            document
+                .get_source_for_range(statement.range())
+                .trim_end_matches('.')
+                .to_string(),
+            "show".to_string(),
+            SymbolKind::INTERFACE,
+            statement,
+            &first,
+        )),
+        "CONST" => {
+            let identifier = statement
+                .child(1)
+                .filter(|node| node.kind() == "identifier")?;
+
+            Some(create_symbol(
+                document.get_source_for_range(identifier.range()),
+                document.get_source_for_range(statement.range()),
+                SymbolKind::CONSTANT,
+                statement,
+                &identifier,
+            ))
+        }
+        "EXTERNAL" => {
+            let atom = statement.child(1).filter(|node| node.kind() == "atom")?;
+
+            Some(create_symbol(
+                get_atom_signature(document, &atom)?,
+                "external".to_string(),
+                SymbolKind::PROPERTY,
+                statement,
+                &atom,
+            ))
+        }
+        "INCLUDE" => Some(create_symbol(
+            document
+                .get_source_for_range(statement.range())
+                .trim_end_matches('.')
+                .to_string(),
+            "include".to_string(),
+            SymbolKind::FILE,
+            statement,
+            &first,
+        )),
+        _ => None,
+    }
+}
+
+/**
+ * Find the signatures of the atoms in the head of a statement, conditions are not part of the head.
+ * Also returns the first atom found, which is used to select the statement
+ */
+fn get_head_signatures<'a>(
+    document: &DocumentData,
+    head: &Node<'a>,
+) -> (Vec<String>, Option<Node<'a>>) {
+    let mut signatures: Vec<String> = Vec::new();
+    let mut first_atom = None;
+    let mut nodes = vec![*head];
+
+    while let Some(node) = nodes.pop() {
+        match node.kind() {
+            "optcondition" | "litvec" => continue,
+            "atom" => {
+                if let Some(signature) = get_atom_signature(document, &node) {
+                    if first_atom.is_none() {
+                        first_atom = Some(node);
+                    }
+                    if !signatures.contains(&signature) {
+                        signatures.push(signature);
+                    }
+                }
+            }
+            _ => {
+                // Push the children in reverse so they are visited from left to right
+                let children: Vec<Node> = node.children(&mut node.walk()).collect();
+                nodes.extend(children.into_iter().rev());
+            }
+        }
+    }
+
+    (signatures, first_atom)
+}
+
+/**
+ * Get the signature of an atom in the form of p/n, classically negated atoms get a leading '-'
+ */
+fn get_atom_signature(document: &DocumentData, atom: &Node) -> Option<String> {
+    let first = atom.child(0)?;
+
+    if first.kind() == "SUB" {
+        let identifier = atom.child(1)?;
+        let arity = if atom.child_count() <= 2 {
+            0
+        } else {
+            document
+                .semantics
+                .predicate_semantics
+                .get_predicates_arity_for_node(&atom.child(3)?.id())
+                + 1
+        };
+
+        return Some(format!(
+            "-{}/{}",
+            document.get_source_for_range(identifier.range()),
+            arity
+        ));
+    }
+
+    let arity = document
+        .semantics
+        .predicate_semantics
+        .get_arity_for_predicate_node(atom);
+
+    Some(format!(
+        "{}/{}",
+        document.get_source_for_range(first.range()),
+        arity
+    ))
+}
+
+/**
+ * Create a symbol for a statement
+ */
+#[allow(deprecated)]
+fn create_symbol(
+    name: String,
+    detail: String,
+    kind: SymbolKind,
+    statement: &Node,
+    selection: &Node,
+) -> DocumentSymbol {
+    DocumentSymbol {
+        name,
+        detail: Some(detail),
+        kind,
+        tags: None,
+        deprecated: None,
+        range: to_lsp_range(statement.range()),
+        selection_range: to_lsp_range(selection.range()),
+        children: None,
+    }
+}
+
+#[cfg(test)]
+fn get_test_symbols(source: &str) -> Vec<DocumentSymbol> {
+    let params = DocumentSymbolParams {
+        text_document: TextDocumentIdentifier::new(Url::from_str("file://test.lp").unwrap()),
+        work_done_progress_params: Default::default(),
+        partial_result_params: Default::default(),
+    };
+
+    match handle(&create_test_document(source.to_string()), &params).unwrap() {
+        DocumentSymbolResponse::Nested(symbols) => symbols,
+        DocumentSymbolResponse::Flat(_) => panic!("Expected nested symbols"),
+    }
+}
+
+#[test]
+fn statements_should_be_named_by_their_head() {
+    let symbols = get_test_symbols(
+        "a(1).\n{ b(X) : a(X) } = 1.\nc(X) ; d :- b(X).\n:- c(1).\n#show c/1.\n#const n = 3.\n#minimize { 1,X : b(X) }.\n:~ b(X). [1,X]",
+    );
+
+    let names: Vec<(&str, Option<&str>)> = symbols
+        .iter()
+        .map(|symbol| (symbol.name.as_str(), symbol.detail.as_deref()))
+        .collect();
+
+    assert_eq!(
+        names,
+        vec![
+            ("a/1", Some("fact")),
+            ("b/1", Some("rule")),
+            ("c/1, d/0", Some("rule")),
+            (":-", Some("constraint")),
+            ("#show c/1", Some("show")),
+            ("n", Some("#const n = 3.")),
+            ("#minimize", Some("optimization")),
+            (":~", Some("weak constraint")),
+        ]
+    );
+}
+
+#[test]
+fn program_parts_should_contain_their_statements() {
+    let symbols =
+        get_test_symbols("a.\n#program step(t).\nb(t).\nc(t) :- b(t).\n#program check.\n:- a.");
+
+    assert_eq!(symbols.len(), 3);
+    assert_eq!(symbols[1].name, "#program step(t)");
+    assert_eq!(symbols[1].children.as_ref().unwrap().len(), 2);
+    assert_eq!(symbols[1].range.end.line, 3);
+    assert_eq!(symbols[2].children.as_ref().unwrap().len(), 1);
+}
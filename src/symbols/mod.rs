@@ -0,0 +1,2 @@
+pub mod document_symbol;
+pub mod workspace_symbol;
@@ -0,0 +1,187 @@
+use std::collections::HashMap;
+
+use tower_lsp::lsp_types::{Location, SymbolInformation, SymbolKind, Url, WorkspaceSymbolParams};
+
+use crate::{
+    rename::predicate::get_identifier_range,
+    semantics::predicate_occurence_semantics::PredicateOccurenceLocation,
+    utils::range::to_lsp_range, workspace::program::Program,
+};
+
+#[cfg(test)]
+use crate::{document::DocumentData, test_utils::create_test_document};
+#[cfg(test)]
+use std::str::FromStr;
+
+/**
+ * Search the predicates and constants of every indexed document.
+ * A predicate is reported once per document at its first definition, so large instances do not flood the results
+ */
+pub fn handle(program: &Program, params: &WorkspaceSymbolParams) -> Option<Vec<SymbolInformation>> {
+    let query = params.query.to_lowercase();
+    let mut symbols: Vec<(usize, SymbolInformation)> = Vec::new();
+
+    for refmulti in program.predicate_semantics.predicates.iter() {
+        let (identifier, arity) = refmulti.key();
+        let name = format!("{}/{}", identifier, arity);
+
+        let score = match fuzzy_score(&query, &name.to_lowercase()) {
+            Some(score) => score,
+            None => continue,
+        };
+
+        // The first definition of the predicate in each document
+        let mut definitions: HashMap<Url, tree_sitter::Range> = HashMap::new();
+        for occurence in refmulti.value().iter() {
+            if occurence.location != PredicateOccurenceLocation::Head {
+                continue;
+            }
+
+            let range = match program
+                .get_document(&occurence.uri)
+                .and_then(|document| get_identifier_range(document, occurence.range))
+            {
+                Some(range) => range,
+                None => continue,
+            };
+
+            let definition = definitions.entry(occurence.uri.clone()).or_insert(range);
+            if range.start_byte < definition.start_byte {
+                *definition = range;
+            }
+        }
+
+        for (uri, range) in definitions {
+            symbols.push((
+                score,
+                create_symbol_information(name.clone(), SymbolKind::FUNCTION, uri, range),
+            ));
+        }
+    }
+
+    for document in &program.documents {
+        let root = document.tree.root_node();
+
+        for statement in root.children(&mut root.walk()) {
+            if statement.child(0).map(|child| child.kind()) != Some("CONST") {
+                continue;
+            }
+
+            let identifier = match statement.child(1) {
+                Some(identifier) if identifier.kind() == "identifier" => identifier,
+                _ => continue,
+            };
+
+            let name = document.get_source_for_range(identifier.range());
+            if let Some(score) = fuzzy_score(&query, &name.to_lowercase()) {
+                symbols.push((
+                    score,
+                    create_symbol_information(
+                        name,
+                        SymbolKind::CONSTANT,
+                        document.uri.clone(),
+                        identifier.range(),
+                    ),
+                ));
+            }
+        }
+    }
+
+    symbols.sort_by(|(score_a, symbol_a), (score_b, symbol_b)| {
+        score_a
+            .cmp(score_b)
+            .then_with(|| symbol_a.name.cmp(&symbol_b.name))
+            .then_with(|| {
+                symbol_a
+                    .location
+                    .uri
+                    .as_str()
+                    .cmp(symbol_b.location.uri.as_str())
+            })
+    });
+
+    Some(symbols.into_iter().map(|(_, symbol)| symbol).collect())
+}
+
+/**
+ * Match the characters of the query in order against a candidate, lower scores are better matches.
+ * Every character skipped in the candidate adds to the score, returns None if the query does not match
+ */
+fn fuzzy_score(query: &str, candidate: &str) -> Option<usize> {
+    let mut score = 0;
+    let mut characters = candidate.chars();
+
+    for query_character in query.chars() {
+        loop {
+            match characters.next() {
+                Some(character) if character == query_character => break,
+                Some(_) => score += 1,
+                None => return None,
+            }
+        }
+    }
+
+    Some(score)
+}
+
+/**
+ * Create the information for a symbol found in the workspace
+ */
+#[allow(deprecated)]
+fn create_symbol_information(
+    name: String,
+    kind: SymbolKind,
+    uri: Url,
+    range: tree_sitter::Range,
+) -> SymbolInformation {
+    SymbolInformation {
+        name,
+        kind,
+        tags: None,
+        deprecated: None,
+        location: Location::new(uri, to_lsp_range(range)),
+        container_name: None,
+    }
+}
+
+#[test]
+fn fuzzy_score_should_prefer_closer_matches() {
+    assert_eq!(fuzzy_score("sched", "schedule/5"), Some(0));
+    assert_eq!(fuzzy_score("sdl", "schedule/5"), Some(4));
+    assert_eq!(fuzzy_score("", "schedule/5"), Some(0));
+    assert_eq!(fuzzy_score("plan", "schedule/5"), None);
+}
+
+#[test]
+fn workspace_symbols_should_contain_definitions_and_constants() {
+    let encoding = create_test_document(
+        "#const horizon = 10.\nschedule(T,M) :- task(T), machine(M), T < horizon.".to_string(),
+    );
+    let instance = DocumentData::from_source(
+        Url::from_str("file:///instance.lp").unwrap(),
+        "task(1). task(2). machine(a).",
+        1,
+    );
+
+    let program = Program::new(vec![encoding, instance]);
+    let params = WorkspaceSymbolParams {
+        query: "h".to_string(),
+        work_done_progress_params: Default::default(),
+        partial_result_params: Default::default(),
+    };
+
+    let symbols = handle(&program, &params).unwrap();
+    let names: Vec<(&str, &str)> = symbols
+        .iter()
+        .map(|symbol| (symbol.name.as_str(), symbol.location.uri.as_str()))
+        .collect();
+
+    assert_eq!(
+        names,
+        vec![
+            ("horizon", "file://test.lp/"),
+            ("schedule/2", "file://test.lp/"),
+            ("machine/1", "file:///instance.lp"),
+        ]
+    );
+}
@@ -1,4 +1,4 @@
-use std::str::FromStr;
+use std::{path::PathBuf, str::FromStr};
 
 use crate::document::DocumentData;
 
@@ -23,3 +23,29 @@ pub fn create_test_document(source: String) -> DocumentData {
     doc.generate_semantics(None);
     doc
 }
+
+/**
+ * A folder in the temporary directory that only one test uses, it is deleted when the test ends
+ */
+pub struct TestFolder {
+    pub path: PathBuf,
+}
+
+impl TestFolder {
+    /**
+     * Create an empty folder, leftovers of an earlier run are removed first
+     */
+    pub fn new(name: &str) -> TestFolder {
+        let path = std::env::temp_dir().join(format!("asp_lsp_{}_{}", name, std::process::id()));
+        let _ = std::fs::remove_dir_all(&path);
+        std::fs::create_dir_all(&path).unwrap();
+
+        TestFolder { path }
+    }
+}
+
+impl Drop for TestFolder {
+    fn drop(&mut self) {
+        let _ = std::fs::remove_dir_all(&self.path);
+    }
+}
@@ -0,0 +1,14 @@
+use tower_lsp::lsp_types::{Position, Range};
+
+/**
+ * Convert a range in the abstract syntax tree into a range that can be send to the client
+ */
+pub fn to_lsp_range(range: tree_sitter::Range) -> Range {
+    Range::new(
+        Position::new(
+            range.start_point.row as u32,
+            range.start_point.column as u32,
+        ),
+        Position::new(range.end_point.row as u32, range.end_point.column as u32),
+    )
+}
@@ -0,0 +1,610 @@
+use std::collections::{BTreeSet, HashMap, VecDeque};
+
+use tower_lsp::lsp_types::Url;
+use tree_sitter::{Node, Range};
+
+use crate::document::DocumentData;
+
+use super::program::Program;
+
+#[cfg(test)]
+use crate::test_utils::create_test_document;
+
+/**
+ * A predicate in the dependency graph, identified by its name and arity
+ */
+pub type Predicate = (String, usize);
+
+/**
+ * The predicate dependency graph of a program, a predicate in the head of a rule depends on every predicate of its body
+ */
+#[derive(Clone, Debug)]
+pub struct DependencyGraph {
+    /**
+     * All predicates that occur in a rule of the program
+     */
+    pub predicates: BTreeSet<Predicate>,
+
+    /**
+     * Every occurence of a body predicate in a rule, there can be multiple dependencies between the same predicates
+     */
+    pub dependencies: Vec<Dependency>,
+
+    /**
+     * Every occurence of a predicate in the head of a rule
+     */
+    pub definitions: Vec<Definition>,
+
+    /**
+     * The indices of the dependencies leaving each predicate
+     */
+    edges: HashMap<Predicate, Vec<usize>>,
+}
+
+/**
+ * A dependency of a head predicate on a predicate in the body or in a condition of the same rule
+ */
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct Dependency {
+    pub head: Predicate,
+    pub body: Predicate,
+
+    /**
+     * The body predicate occurs in a literal with default negation
+     */
+    pub negative: bool,
+
+    /**
+     * The body predicate occurs in the elements of a body aggregate
+     */
+    pub aggregate: bool,
+
+    /**
+     * Where the identifier of the body predicate occurs
+     */
+    pub uri: Url,
+    pub range: Range,
+
+    /**
+     * The rule the dependency comes from
+     */
+    pub statement: Range,
+}
+
+/**
+ * An occurence of a predicate in the head of a rule
+ */
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct Definition {
+    pub predicate: Predicate,
+
+    /**
+     * The #program part the rule belongs to, rules before the first #program statement are part of base
+     */
+    pub part: String,
+
+    /**
+     * Where the identifier of the head predicate occurs
+     */
+    pub uri: Url,
+    pub range: Range,
+
+    /**
+     * The rule that defines the predicate
+     */
+    pub statement: Range,
+}
+
+impl DependencyGraph {
+    /**
+     * Build the dependency graph from the rules of all documents of a program
+     */
+    pub fn new(program: &Program) -> DependencyGraph {
+        let mut graph = DependencyGraph {
+            predicates: BTreeSet::new(),
+            dependencies: Vec::new(),
+            definitions: Vec::new(),
+            edges: HashMap::new(),
+        };
+
+        for document in &program.documents {
+            graph.add_document(document);
+        }
+
+        for (index, dependency) in graph.dependencies.iter().enumerate() {
+            graph
+                .edges
+                .entry(dependency.head.clone())
+                .or_default()
+                .push(index);
+        }
+
+        graph
+    }
+
+    fn add_document(&mut self, document: &DocumentData) {
+        let root = document.tree.root_node();
+        let mut part = "base".to_string();
+
+        for statement in root.children(&mut root.walk()) {
+            if statement.kind() != "statement" {
+                continue;
+            }
+
+            if statement.child(0).map(|child| child.kind()) == Some("BLOCK") {
+                part = get_part_name(document, &statement).unwrap_or(part);
+                continue;
+            }
+
+            // Directives and constraints have no head and define nothing
+            if !statement
+                .children(&mut statement.walk())
+                .any(|child| child.kind() == "head")
+            {
+                continue;
+            }
+
+            let mut heads = Vec::new();
+            let mut bodies = Vec::new();
+            collect_atoms(statement, statement, &mut heads, &mut bodies);
+
+            for atom in heads.iter().chain(bodies.iter()) {
+                self.predicates.insert(get_predicate(document, atom));
+            }
+
+            for head_atom in &heads {
+                let head_predicate = get_predicate(document, head_atom);
+                self.definitions.push(Definition {
+                    predicate: head_predicate.clone(),
+                    part: part.clone(),
+                    uri: document.uri.clone(),
+                    range: get_identifier(head_atom).unwrap().range(),
+                    statement: statement.range(),
+                });
+
+                for body_atom in &bodies {
+                    self.dependencies.push(Dependency {
+                        head: head_predicate.clone(),
+                        body: get_predicate(document, body_atom),
+                        negative: is_negated(body_atom),
+                        aggregate: has_ancestor(body_atom, statement, "lubodyaggregate"),
+                        uri: document.uri.clone(),
+                        range: get_identifier(body_atom).unwrap().range(),
+                        statement: statement.range(),
+                    });
+                }
+            }
+        }
+    }
+
+    /**
+     * Get the dependencies of a predicate, these are the edges leaving it in the graph
+     */
+    pub fn get_dependencies(&self, head: &Predicate) -> Vec<&Dependency> {
+        self.edges
+            .get(head)
+            .map(|indices| {
+                indices
+                    .iter()
+                    .map(|index| &self.dependencies[*index])
+                    .collect()
+            })
+            .unwrap_or_default()
+    }
+
+    /**
+     * Calculate the strongly connected components of the graph with Tarjan's algorithm,
+     * two predicates are in the same component if they depend on each other
+     */
+    pub fn get_components(&self) -> Vec<Vec<Predicate>> {
+        let predicates: Vec<&Predicate> = self.predicates.iter().collect();
+        let ids: HashMap<&Predicate, usize> = predicates
+            .iter()
+            .enumerate()
+            .map(|(id, predicate)| (*predicate, id))
+            .collect();
+
+        let mut tarjan = Tarjan {
+            successors: predicates
+                .iter()
+                .map(|predicate| {
+                    self.get_dependencies(predicate)
+                        .iter()
+                        .filter_map(|dependency| ids.get(&dependency.body).copied())
+                        .collect()
+                })
+                .collect(),
+            index: vec![None; predicates.len()],
+            lowlink: vec![0; predicates.len()],
+            on_stack: vec![false; predicates.len()],
+            stack: Vec::new(),
+            components: Vec::new(),
+            next_index: 0,
+        };
+
+        for id in 0..predicates.len() {
+            if tarjan.index[id].is_none() {
+                tarjan.visit(id);
+            }
+        }
+
+        tarjan
+            .components
+            .into_iter()
+            .map(|component| {
+                let mut component: Vec<Predicate> = component
+                    .into_iter()
+                    .map(|id| predicates[id].clone())
+                    .collect();
+                component.sort();
+                component
+            })
+            .collect()
+    }
+
+    /**
+     * Map every predicate to the number of its strongly connected component
+     */
+    pub fn get_component_ids(&self) -> HashMap<Predicate, usize> {
+        let mut component_ids = HashMap::new();
+
+        for (id, component) in self.get_components().into_iter().enumerate() {
+            for predicate in component {
+                component_ids.insert(predicate, id);
+            }
+        }
+
+        component_ids
+    }
+
+    /**
+     * Find a cycle that starts with a dependency and leads back to its head.
+     * If odd is set, the cycle has to go through an odd number of negative dependencies
+     */
+    pub fn find_cycle<'a>(
+        &'a self,
+        dependency: &'a Dependency,
+        odd: bool,
+    ) -> Option<Vec<&'a Dependency>> {
+        // Search a path from the body back to the head, remembering how many negations were passed modulo 2
+        let start = (&dependency.body, dependency.negative);
+
+        let mut predecessors: HashMap<(&Predicate, bool), &Dependency> = HashMap::new();
+        let mut queue = VecDeque::from([start]);
+        let mut visited = BTreeSet::from([start]);
+
+        while let Some((predicate, parity)) = queue.pop_front() {
+            if predicate == &dependency.head && (parity || !odd) {
+                let mut cycle = vec![];
+                let mut current = (predicate, parity);
+                while current != start {
+                    let edge = predecessors[&current];
+                    cycle.push(edge);
+                    current = (&edge.head, current.1 ^ edge.negative);
+                }
+                cycle.push(dependency);
+                cycle.reverse();
+                return Some(cycle);
+            }
+
+            for next in self.get_dependencies(predicate) {
+                let state = (&next.body, parity ^ next.negative);
+                if visited.insert(state) {
+                    predecessors.insert(state, next);
+                    queue.push_back(state);
+                }
+            }
+        }
+
+        None
+    }
+}
+
+/**
+ * The state of Tarjan's algorithm, predicates are numbered by their position in the graph
+ */
+struct Tarjan {
+    successors: Vec<Vec<usize>>,
+    index: Vec<Option<usize>>,
+    lowlink: Vec<usize>,
+    on_stack: Vec<bool>,
+    stack: Vec<usize>,
+    components: Vec<Vec<usize>>,
+    next_index: usize,
+}
+
+impl Tarjan {
+    /**
+     * Visit every predicate reachable from a root, the depth first search keeps its own stack
+     * so that long chains of dependencies can not overflow the call stack
+     */
+    fn visit(&mut self, root: usize) {
+        // Every frame is a predicate together with the number of its successors that were looked at
+        let mut frames = vec![(root, 0)];
+        self.enter(root);
+
+        while let Some((predicate, position)) = frames.last_mut() {
+            let predicate = *predicate;
+
+            if let Some(&successor) = self.successors[predicate].get(*position) {
+                *position += 1;
+
+                match self.index[successor] {
+                    None => {
+                        self.enter(successor);
+                        frames.push((successor, 0));
+                    }
+                    Some(index) if self.on_stack[successor] => {
+                        self.lowlink[predicate] = self.lowlink[predicate].min(index);
+                    }
+                    Some(_) => {}
+                }
+                continue;
+            }
+
+            frames.pop();
+            if let Some((parent, _)) = frames.last() {
+                self.lowlink[*parent] = self.lowlink[*parent].min(self.lowlink[predicate]);
+            }
+
+            if Some(self.lowlink[predicate]) == self.index[predicate] {
+                let mut component = Vec::new();
+                while let Some(member) = self.stack.pop() {
+                    self.on_stack[member] = false;
+                    component.push(member);
+                    if member == predicate {
+                        break;
+                    }
+                }
+                self.components.push(component);
+            }
+        }
+    }
+
+    fn enter(&mut self, predicate: usize) {
+        self.index[predicate] = Some(self.next_index);
+        self.lowlink[predicate] = self.next_index;
+        self.next_index += 1;
+        self.on_stack[predicate] = true;
+        self.stack.push(predicate);
+    }
+}
+
+/**
+ * Sort the atoms of a rule into the ones in the head and the ones the head depends on,
+ * atoms in the conditions of head elements are dependencies as well
+ */
+fn collect_atoms<'a>(
+    node: Node<'a>,
+    statement: Node<'a>,
+    heads: &mut Vec<Node<'a>>,
+    bodies: &mut Vec<Node<'a>>,
+) {
+    for child in node.children(&mut node.walk()) {
+        if child.kind() != "atom" {
+            collect_atoms(child, statement, heads, bodies);
+            continue;
+        }
+
+        if get_identifier(&child).is_none() {
+            continue;
+        }
+
+        if has_ancestor(&child, statement, "head")
+            && !has_ancestor(&child, statement, "litvec")
+            && !has_ancestor(&child, statement, "optcondition")
+        {
+            heads.push(child);
+        } else {
+            bodies.push(child);
+        }
+    }
+}
+
+/**
+ * Get the name of a #program statement together with its parameters, e.g. step(t)
+ */
+fn get_part_name(document: &DocumentData, statement: &Node) -> Option<String> {
+    // A #program statement consists of: BLOCK identifier [LPAREN idlist RPAREN] DOT
+    let identifier = statement.child(1)?;
+    let last = statement.child(statement.child_count().checked_sub(2)?)?;
+    if identifier.kind() != "identifier" {
+        return None;
+    }
+
+    Some(document.get_source_for_range(tree_sitter::Range {
+        start_byte: identifier.start_byte(),
+        end_byte: last.end_byte(),
+        start_point: identifier.start_position(),
+        end_point: last.end_position(),
+    }))
+}
+
+/**
+ * Get the identifier of an atom, classically negated atoms like -a(X) start with a minus in front of it
+ */
+fn get_identifier<'a>(atom: &Node<'a>) -> Option<Node<'a>> {
+    let first = atom.child(0)?;
+    let identifier = match first.kind() {
+        "SUB" => atom.child(1)?,
+        _ => first,
+    };
+
+    (identifier.kind() == "identifier").then_some(identifier)
+}
+
+/**
+ * Get the predicate of an atom, a classically negated predicate is a predicate of its own named like -a
+ */
+fn get_predicate(document: &DocumentData, atom: &Node) -> Predicate {
+    let identifier = get_identifier(atom).unwrap();
+    let arity = atom
+        .children(&mut atom.walk())
+        .find(|child| child.kind() == "argvec")
+        .map_or(0, |arguments| {
+            document
+                .semantics
+                .predicate_semantics
+                .get_predicates_arity_for_node(&arguments.id())
+                + 1
+        });
+
+    let name = document.get_source_for_range(identifier.range());
+    match atom.child(0).unwrap().kind() {
+        "SUB" => (format!("-{}", name), arity),
+        _ => (name, arity),
+    }
+}
+
+/**
+ * Check if an atom is part of a literal with default negation, double negation is not monotone either
+ */
+fn is_negated(atom: &Node) -> bool {
+    atom.parent().is_some_and(|literal| {
+        literal.kind() == "literal"
+            && literal
+                .children(&mut literal.walk())
+                .any(|child| child.kind() == "NOT")
+    })
+}
+
+/**
+ * Check if a node has an ancestor of a kind, the search stops at the boundary node
+ */
+fn has_ancestor(node: &Node, boundary: Node, kind: &str) -> bool {
+    let mut current = node.parent();
+    while let Some(parent) = current {
+        if parent.kind() == kind {
+            return true;
+        }
+        if parent.id() == boundary.id() {
+            return false;
+        }
+        current = parent.parent();
+    }
+    false
+}
+
+#[cfg(test)]
+fn get_test_graph(source: &str) -> DependencyGraph {
+    DependencyGraph::new(&Program::new(vec![create_test_document(
+        source.to_string(),
+    )]))
+}
+
+#[test]
+fn dependencies_should_be_marked_negative_and_aggregate() {
+    let graph = get_test_graph(
+        "a(X) :- b(X), not c(X).\nd :- #count{ X : e(X), not f(X) } > 1.\n{ g(X) : h(X) } :- i.\n:- a(1).",
+    );
+
+    let mut dependencies: Vec<(String, String, bool, bool)> = graph
+        .dependencies
+        .iter()
+        .map(|dependency| {
+            (
+                format!("{}/{}", dependency.head.0, dependency.head.1),
+                format!("{}/{}", dependency.body.0, dependency.body.1),
+                dependency.negative,
+                dependency.aggregate,
+            )
+        })
+        .collect();
+    dependencies.sort();
+
+    let expected: Vec<(String, String, bool, bool)> = [
+        ("a/1", "b/1", false, false),
+        ("a/1", "c/1", true, false),
+        ("d/0", "e/1", false, true),
+        ("d/0", "f/1", true, true),
+        ("g/1", "h/1", false, false),
+        ("g/1", "i/0", false, false),
+    ]
+    .iter()
+    .map(|(head, body, negative, aggregate)| {
+        (head.to_string(), body.to_string(), *negative, *aggregate)
+    })
+    .collect();
+
+    assert_eq!(dependencies, expected);
+    assert_eq!(graph.predicates.len(), 9);
+}
+
+#[test]
+fn definitions_should_belong_to_their_program_part() {
+    let graph = get_test_graph("a. #program step(t). b(t) :- a. #program check. :- b(1).");
+
+    let parts: Vec<(String, String)> = graph
+        .definitions
+        .iter()
+        .map(|definition| (definition.predicate.0.clone(), definition.part.clone()))
+        .collect();
+
+    assert_eq!(
+        parts,
+        vec![
+            ("a".to_string(), "base".to_string()),
+            ("b".to_string(), "step(t)".to_string()),
+        ]
+    );
+}
+
+#[test]
+fn components_should_contain_mutually_dependent_predicates() {
+    let graph = get_test_graph("a :- b. b :- a, c. c :- d. d :- c, not e. e.");
+
+    let mut components = graph.get_components();
+    components.sort();
+
+    assert_eq!(
+        components,
+        vec![
+            vec![("a".to_string(), 0), ("b".to_string(), 0)],
+            vec![("c".to_string(), 0), ("d".to_string(), 0)],
+            vec![("e".to_string(), 0)],
+        ]
+    );
+}
+
+#[test]
+fn odd_cycles_should_only_be_found_through_an_odd_number_of_negations() {
+    let graph = get_test_graph("a :- not b. b :- not a. c :- not d. d :- c.");
+
+    let a_to_b = &graph.get_dependencies(&("a".to_string(), 0))[0];
+    assert_eq!(graph.find_cycle(a_to_b, false).unwrap().len(), 2);
+    assert!(graph.find_cycle(a_to_b, true).is_none());
+
+    let c_to_d = &graph.get_dependencies(&("c".to_string(), 0))[0];
+    let cycle = graph.find_cycle(c_to_d, true).unwrap();
+    assert_eq!(cycle.len(), 2);
+    assert_eq!(cycle[1].head, ("d".to_string(), 0));
+    assert_eq!(cycle[1].body, ("c".to_string(), 0));
+}
+
+#[test]
+fn classically_negated_atoms_should_be_predicates_of_their_own() {
+    let graph = get_test_graph("a(X) :- b(X), not -a(X). -a(X) :- a(X). -c(1,2) :- b(1).");
+
+    let mut components = graph.get_components();
+    components.sort();
+
+    assert_eq!(
+        components,
+        vec![
+            vec![("-a".to_string(), 1), ("a".to_string(), 1)],
+            vec![("-c".to_string(), 2)],
+            vec![("b".to_string(), 1)],
+        ]
+    );
+}
+
+#[test]
+fn components_should_be_found_in_long_chains_of_dependencies() {
+    let source: String = (1..2000)
+        .map(|i| format!("p{} :- p{}.\n", i, i - 1))
+        .chain(std::iter::once("p0 :- p1999.".to_string()))
+        .collect();
+    let graph = get_test_graph(&source);
+
+    assert_eq!(graph.get_components().len(), 1);
+}
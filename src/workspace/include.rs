@@ -0,0 +1,139 @@
+use std::path::{Component, Path, PathBuf};
+
+use tower_lsp::lsp_types::Url;
+
+use crate::document::DocumentData;
+
+#[cfg(test)]
+use crate::test_utils::{create_test_document, TestFolder};
+
+/**
+ * Find the paths of all files that are included by a document with an #include statement.
+ * Includes of builtin modules like #include <incmode>. are ignored as they are not files
+ */
+pub fn get_included_paths(document: &DocumentData) -> Vec<String> {
+    let root = document.tree.root_node();
+    let mut paths = Vec::new();
+
+    for statement in root.children(&mut root.walk()) {
+        if statement.kind() != "statement" || statement.child_count() < 2 {
+            continue;
+        }
+
+        if statement.child(0).unwrap().kind() != "INCLUDE"
+            || statement.child(1).unwrap().kind() != "STRING"
+        {
+            continue;
+        }
+
+        let string = document.get_source_for_range(statement.child(1).unwrap().range());
+        let path = string.trim_matches('"');
+
+        if !path.is_empty() {
+            paths.push(path.to_string());
+        }
+    }
+
+    paths
+}
+
+/**
+ * Resolve an included path to the uri of a file on disk.
+ * The path is first resolved relative to the including document, afterwards relative to each workspace folder
+ */
+pub fn resolve_include(document_uri: &Url, path: &str, folders: &[PathBuf]) -> Option<Url> {
+    let included = Path::new(path);
+
+    let mut candidates = Vec::new();
+    if included.is_absolute() {
+        candidates.push(included.to_path_buf());
+    } else {
+        if let Some(directory) = document_uri
+            .to_file_path()
+            .ok()
+            .and_then(|file| file.parent().map(Path::to_path_buf))
+        {
+            candidates.push(directory.join(included));
+        }
+
+        for folder in folders {
+            candidates.push(folder.join(included));
+        }
+    }
+
+    candidates
+        .into_iter()
+        .map(|candidate| normalize_path(&candidate))
+        .find(|candidate| candidate.is_file())
+        .and_then(|candidate| Url::from_file_path(candidate).ok())
+}
+
+/**
+ * Remove any '.' and '..' components from a path without touching the file system,
+ * so that a file that is reached through different includes always gets the same uri
+ */
+fn normalize_path(path: &Path) -> PathBuf {
+    let mut normalized = PathBuf::new();
+
+    for component in path.components() {
+        match component {
+            Component::CurDir => {}
+            Component::ParentDir => {
+                normalized.pop();
+            }
+            _ => normalized.push(component),
+        }
+    }
+
+    normalized
+}
+
+#[test]
+fn included_paths_should_be_found() {
+    let doc = create_test_document(
+        "#include \"instance.lp\".\na :- b.\n#include \"../lib/helpers.lp\".".to_string(),
+    );
+
+    assert_eq!(
+        get_included_paths(&doc),
+        vec!["instance.lp".to_string(), "../lib/helpers.lp".to_string()]
+    );
+}
+
+#[test]
+fn builtin_includes_should_be_ignored() {
+    let doc = create_test_document("#include <incmode>.".to_string());
+
+    assert_eq!(get_included_paths(&doc).len(), 0);
+}
+
+#[test]
+fn includes_should_be_resolved_relative_to_the_including_file() {
+    let folder = TestFolder::new("include_relative");
+    let directory = &folder.path;
+    std::fs::create_dir_all(directory.join("lib")).unwrap();
+    std::fs::write(directory.join("lib").join("helpers.lp"), "a.").unwrap();
+
+    let encoding = Url::from_file_path(directory.join("encoding.lp")).unwrap();
+
+    assert_eq!(
+        resolve_include(&encoding, "./lib/../lib/helpers.lp", &[]),
+        Url::from_file_path(directory.join("lib").join("helpers.lp")).ok()
+    );
+    assert_eq!(resolve_include(&encoding, "missing.lp", &[]), None);
+}
+
+#[test]
+fn includes_should_be_resolved_relative_to_the_workspace_folders() {
+    let folder = TestFolder::new("include_workspace");
+    let directory = &folder.path;
+    std::fs::create_dir_all(directory.join("instances")).unwrap();
+    std::fs::write(directory.join("instance.lp"), "a.").unwrap();
+
+    let encoding = Url::from_file_path(directory.join("instances").join("encoding.lp")).unwrap();
+
+    assert_eq!(
+        resolve_include(&encoding, "instance.lp", std::slice::from_ref(directory)),
+        Url::from_file_path(directory.join("instance.lp")).ok()
+    );
+}
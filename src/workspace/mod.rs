@@ -0,0 +1,754 @@
+use std::{
+    collections::{HashMap, HashSet, VecDeque},
+    path::{Path, PathBuf},
+    sync::{
+        atomic::{AtomicUsize, Ordering},
+        Arc, RwLock,
+    },
+    time::Instant,
+};
+
+use dashmap::DashMap;
+use log::info;
+use tower_lsp::lsp_types::Url;
+
+use crate::document::DocumentData;
+
+use self::{
+    include::{get_included_paths, resolve_include},
+    program::Program,
+};
+
+pub mod dependency_graph;
+pub mod include;
+pub mod program;
+
+#[cfg(test)]
+use crate::test_utils::TestFolder;
+
+/**
+ * For every file the files that include it
+ */
+type IncludeIndex = HashMap<String, Vec<Url>>;
+
+/**
+ * The workspace keeps track of the workspace folders and of the encodings that were read from disk,
+ * documents opened in the editor always take precedence over the files on disk
+ */
+#[derive(Debug)]
+pub struct Workspace {
+    pub folders: RwLock<Vec<PathBuf>>,
+    pub files: DashMap<String, DocumentData>,
+
+    /**
+     * Additional folders that are searched for included files, relative paths are relative to the workspace folders
+     */
+    pub include_paths: RwLock<Vec<PathBuf>>,
+
+    /**
+     * Programs that were built before, together with the generation they were built in
+     */
+    programs: DashMap<String, (usize, Arc<Program>)>,
+    workspace_programs: DashMap<String, (usize, Arc<Program>)>,
+    root_programs: DashMap<String, (usize, Arc<Program>)>,
+    indexed_program: RwLock<Option<(usize, Arc<Program>)>>,
+
+    /**
+     * For every file the files that include it, the reverse of the #include statements of all known documents
+     */
+    include_index: RwLock<Option<(usize, Arc<IncludeIndex>)>>,
+
+    /**
+     * Increased whenever a document changes, cached programs of an older generation are outdated
+     */
+    generation: AtomicUsize,
+}
+
+impl Workspace {
+    pub fn new() -> Workspace {
+        Workspace {
+            folders: RwLock::new(Vec::new()),
+            files: DashMap::new(),
+            include_paths: RwLock::new(Vec::new()),
+            programs: DashMap::new(),
+            workspace_programs: DashMap::new(),
+            root_programs: DashMap::new(),
+            indexed_program: RwLock::new(None),
+            include_index: RwLock::new(None),
+            generation: AtomicUsize::new(0),
+        }
+    }
+
+    /**
+     * Throw away the cached programs, this has to be called after a document changed
+     */
+    pub fn invalidate_programs(&self) {
+        self.generation.fetch_add(1, Ordering::SeqCst);
+        self.programs.clear();
+        self.workspace_programs.clear();
+        self.root_programs.clear();
+        *self.indexed_program.write().unwrap() = None;
+        *self.include_index.write().unwrap() = None;
+    }
+
+    /**
+     * Get a program from a cache or build it if the cached one is outdated.
+     * The generation is read before building, so a program built from documents that changed meanwhile is never used
+     */
+    fn get_cached_program(
+        &self,
+        cache: &DashMap<String, (usize, Arc<Program>)>,
+        uri: &Url,
+        build: impl FnOnce() -> Program,
+    ) -> Arc<Program> {
+        let generation = self.generation.load(Ordering::SeqCst);
+
+        if let Some(cached) = cache.get(&uri.to_string()) {
+            if cached.0 == generation {
+                return cached.1.clone();
+            }
+        }
+
+        let program = Arc::new(build());
+        cache.insert(uri.to_string(), (generation, program.clone()));
+
+        program
+    }
+
+    /**
+     * Replace the workspace folders, uris that are not on the file system are ignored
+     */
+    pub fn set_folders(&self, uris: Vec<Url>) {
+        let folders = uris
+            .iter()
+            .filter_map(|uri| uri.to_file_path().ok())
+            .collect();
+
+        *self.folders.write().unwrap() = folders;
+        self.invalidate_programs();
+    }
+
+    /**
+     * Add workspace folders and return the folders that were not known before
+     */
+    pub fn add_folders(&self, uris: Vec<Url>) -> Vec<PathBuf> {
+        let mut folders = self.folders.write().unwrap();
+        let mut added = Vec::new();
+
+        for folder in uris.iter().filter_map(|uri| uri.to_file_path().ok()) {
+            if !folders.contains(&folder) {
+                folders.push(folder.clone());
+                added.push(folder);
+            }
+        }
+
+        added
+    }
+
+    /**
+     * Remove workspace folders and forget every file that was read from them
+     */
+    pub fn remove_folders(&self, uris: Vec<Url>) {
+        let removed: Vec<PathBuf> = uris
+            .iter()
+            .filter_map(|uri| uri.to_file_path().ok())
+            .collect();
+
+        self.folders
+            .write()
+            .unwrap()
+            .retain(|folder| !removed.contains(folder));
+
+        self.files.retain(|uri, _| {
+            match Url::parse(uri).ok().and_then(|uri| uri.to_file_path().ok()) {
+                Some(path) => !removed.iter().any(|folder| path.starts_with(folder)),
+                None => true,
+            }
+        });
+        self.invalidate_programs();
+    }
+
+    /**
+     * Replace the additional folders that are searched for included files
+     */
+    pub fn set_include_paths(&self, paths: Vec<PathBuf>) {
+        *self.include_paths.write().unwrap() = paths;
+        self.invalidate_programs();
+    }
+
+    /**
+     * Get the folders an included file is searched in, first the workspace folders and then the include paths
+     */
+    pub fn get_search_paths(&self) -> Vec<PathBuf> {
+        let mut paths = self.get_folders();
+
+        for include_path in self.include_paths.read().unwrap().iter() {
+            if include_path.is_absolute() {
+                paths.push(include_path.clone());
+                continue;
+            }
+
+            for folder in self.get_folders() {
+                paths.push(folder.join(include_path));
+            }
+        }
+
+        paths
+    }
+
+    /**
+     * Get a copy of the workspace folders
+     */
+    pub fn get_folders(&self) -> Vec<PathBuf> {
+        self.folders.read().unwrap().clone()
+    }
+
+    /**
+     * Read a file from disk, parse it and remember it in the workspace
+     */
+    pub fn load_file(&self, uri: &Url) -> Option<DocumentData> {
+        let path = uri.to_file_path().ok()?;
+        let source = match std::fs::read_to_string(&path) {
+            Ok(source) => source,
+            Err(error) => {
+                info!("Could not read {}: {}", path.display(), error);
+                return None;
+            }
+        };
+
+        let document = DocumentData::from_source(uri.clone(), &source, 0);
+        self.files.insert(uri.to_string(), document.clone());
+
+        Some(document)
+    }
+
+    /**
+     * Read every encoding inside of the folders so that they can be used before they are opened
+     */
+    pub fn index_folders(&self, folders: Vec<PathBuf>) {
+        let time = Instant::now();
+
+        let mut number_of_files = 0;
+        for folder in folders {
+            for path in find_encodings(&folder) {
+                if let Ok(uri) = Url::from_file_path(&path) {
+                    if self.load_file(&uri).is_some() {
+                        number_of_files += 1;
+                    }
+                }
+            }
+        }
+
+        let duration = time.elapsed();
+        info!(
+            "Time needed for indexing {} workspace files: {:?}",
+            number_of_files, duration
+        );
+
+        self.invalidate_programs();
+    }
+
+    /**
+     * Read a file again after it was saved or closed in the editor, so that it stays part of the workspace.
+     * A file that can not be read anymore is forgotten
+     */
+    pub fn reload_file(&self, uri: &Url) {
+        if self.load_file(uri).is_none() {
+            self.files.remove(&uri.to_string());
+        }
+        self.invalidate_programs();
+    }
+
+    /**
+     * Forget a file that was read from disk, the next time it is needed it will be read again
+     */
+    pub fn invalidate_file(&self, uri: &Url) {
+        self.files.remove(&uri.to_string());
+        self.invalidate_programs();
+    }
+
+    /**
+     * Get a document either from the documents opened in the editor or from disk
+     */
+    pub fn get_document(
+        &self,
+        uri: &Url,
+        open_documents: &DashMap<String, DocumentData>,
+    ) -> Option<DocumentData> {
+        if let Some(document) = open_documents.get(&uri.to_string()) {
+            return Some(document.value().clone());
+        }
+
+        if let Some(document) = self.files.get(&uri.to_string()) {
+            return Some(document.value().clone());
+        }
+
+        self.load_file(uri)
+    }
+
+    /**
+     * Get the program for a document by following all of its #include statements
+     */
+    pub fn get_program(
+        &self,
+        uri: &Url,
+        open_documents: &DashMap<String, DocumentData>,
+    ) -> Arc<Program> {
+        self.get_cached_program(&self.programs, uri, || {
+            Program::new(self.collect_included_documents(uri, open_documents))
+        })
+    }
+
+    /**
+     * Collect a document and every document it includes, directly or through other includes
+     */
+    fn collect_included_documents(
+        &self,
+        uri: &Url,
+        open_documents: &DashMap<String, DocumentData>,
+    ) -> Vec<DocumentData> {
+        let folders = self.get_search_paths();
+        let mut visited = HashSet::new();
+        let mut queue = VecDeque::from([uri.clone()]);
+        let mut documents = Vec::new();
+
+        while let Some(current) = queue.pop_front() {
+            // Files can be included multiple times or even include each other
+            if !visited.insert(current.to_string()) {
+                continue;
+            }
+
+            let document = match self.get_document(&current, open_documents) {
+                Some(document) => document,
+                None => continue,
+            };
+
+            for path in get_included_paths(&document) {
+                match resolve_include(&current, &path, &folders) {
+                    Some(included) => queue.push_back(included),
+                    None => info!("Could not resolve #include \"{}\" in {}", path, current),
+                }
+            }
+
+            documents.push(document);
+        }
+
+        documents
+    }
+
+    /**
+     * Get the program a document is analyzed in. A file that is included by other files is analyzed within the programs
+     * of the root files that include it, so that definitions and uses in sibling files are seen. The programs of all roots are joined
+     */
+    pub fn get_root_program(
+        &self,
+        uri: &Url,
+        open_documents: &DashMap<String, DocumentData>,
+    ) -> Arc<Program> {
+        self.get_cached_program(&self.root_programs, uri, || {
+            let mut known = HashSet::new();
+            let mut documents = Vec::new();
+
+            for root in self.get_root_files(uri, open_documents) {
+                for document in self.collect_included_documents(&root, open_documents) {
+                    if known.insert(document.uri.to_string()) {
+                        documents.push(document);
+                    }
+                }
+            }
+
+            Program::new(documents)
+        })
+    }
+
+    /**
+     * Find the files that include a file directly or through other files, but are not included themselves.
+     * A file that is not included anywhere is its own root
+     */
+    fn get_root_files(&self, uri: &Url, open_documents: &DashMap<String, DocumentData>) -> Vec<Url> {
+        let include_index = self.get_include_index(open_documents);
+        let mut visited = HashSet::from([uri.to_string()]);
+        let mut queue = VecDeque::from([uri.clone()]);
+        let mut roots = Vec::new();
+
+        while let Some(current) = queue.pop_front() {
+            match include_index.get(current.as_str()) {
+                Some(including) => {
+                    for file in including {
+                        if visited.insert(file.to_string()) {
+                            queue.push_back(file.clone());
+                        }
+                    }
+                }
+                None => roots.push(current),
+            }
+        }
+
+        // Files that only include each other have no root
+        if roots.is_empty() {
+            roots.push(uri.clone());
+        }
+
+        roots
+    }
+
+    /**
+     * Get the reverse include index, it is rebuilt once the documents changed
+     */
+    fn get_include_index(
+        &self,
+        open_documents: &DashMap<String, DocumentData>,
+    ) -> Arc<IncludeIndex> {
+        let generation = self.generation.load(Ordering::SeqCst);
+
+        if let Some((cached_generation, index)) = self.include_index.read().unwrap().as_ref() {
+            if *cached_generation == generation {
+                return index.clone();
+            }
+        }
+
+        let folders = self.get_search_paths();
+        let mut index: IncludeIndex = HashMap::new();
+        let mut add_includes = |document: &DocumentData| {
+            for path in get_included_paths(document) {
+                if let Some(included) = resolve_include(&document.uri, &path, &folders) {
+                    index
+                        .entry(included.to_string())
+                        .or_default()
+                        .push(document.uri.clone());
+                }
+            }
+        };
+
+        for refmulti in open_documents.iter() {
+            add_includes(refmulti.value());
+        }
+        for refmulti in self.files.iter() {
+            if !open_documents.contains_key(refmulti.key()) {
+                add_includes(refmulti.value());
+            }
+        }
+
+        let index = Arc::new(index);
+        *self.include_index.write().unwrap() = Some((generation, index.clone()));
+
+        index
+    }
+
+    /**
+     * Build a program for a document that contains every document known to the workspace,
+     * this is used by features that have to find predicates regardless of #include statements
+     */
+    pub fn get_workspace_program(
+        &self,
+        uri: &Url,
+        open_documents: &DashMap<String, DocumentData>,
+    ) -> Arc<Program> {
+        self.get_cached_program(&self.workspace_programs, uri, || {
+            self.build_workspace_program(uri, open_documents)
+        })
+    }
+
+    fn build_workspace_program(
+        &self,
+        uri: &Url,
+        open_documents: &DashMap<String, DocumentData>,
+    ) -> Program {
+        let mut documents = self.collect_included_documents(uri, open_documents);
+        let mut known: HashSet<String> = documents
+            .iter()
+            .map(|document| document.uri.to_string())
+            .collect();
+
+        for refmulti in open_documents.iter() {
+            if known.insert(refmulti.key().clone()) {
+                documents.push(refmulti.value().clone());
+            }
+        }
+
+        for refmulti in self.files.iter() {
+            if known.insert(refmulti.key().clone()) {
+                documents.push(refmulti.value().clone());
+            }
+        }
+
+        Program::new(documents)
+    }
+
+    /**
+     * Get a program out of every document known to the workspace, open documents take precedence.
+     * It is cached like the other programs, so searching while typing does not rebuild it
+     */
+    pub fn get_indexed_program(
+        &self,
+        open_documents: &DashMap<String, DocumentData>,
+    ) -> Arc<Program> {
+        let generation = self.generation.load(Ordering::SeqCst);
+
+        if let Some((cached_generation, program)) = self.indexed_program.read().unwrap().as_ref() {
+            if *cached_generation == generation {
+                return program.clone();
+            }
+        }
+
+        let program = Arc::new(self.build_indexed_program(open_documents));
+        *self.indexed_program.write().unwrap() = Some((generation, program.clone()));
+
+        program
+    }
+
+    fn build_indexed_program(&self, open_documents: &DashMap<String, DocumentData>) -> Program {
+        let mut documents: Vec<DocumentData> = open_documents
+            .iter()
+            .map(|refmulti| refmulti.value().clone())
+            .collect();
+
+        for refmulti in self.files.iter() {
+            if !open_documents.contains_key(refmulti.key()) {
+                documents.push(refmulti.value().clone());
+            }
+        }
+
+        Program::new(documents)
+    }
+}
+
+/**
+ * Recursively find all encodings (.lp files) in a folder, hidden folders like .git are skipped.
+ * Symbolic links to folders are not followed, so links pointing back up the tree can not loop forever
+ */
+pub fn find_encodings(folder: &Path) -> Vec<PathBuf> {
+    let mut encodings = Vec::new();
+    let mut folders = vec![folder.to_path_buf()];
+
+    while let Some(current) = folders.pop() {
+        let entries = match std::fs::read_dir(&current) {
+            Ok(entries) => entries,
+            Err(error) => {
+                info!("Could not read folder {}: {}", current.display(), error);
+                continue;
+            }
+        };
+
+        for entry in entries.flatten() {
+            let path = entry.path();
+            let is_hidden = entry.file_name().to_string_lossy().starts_with('.');
+            let is_folder = entry
+                .file_type()
+                .is_ok_and(|file_type| file_type.is_dir());
+
+            if is_folder {
+                if !is_hidden {
+                    folders.push(path);
+                }
+            } else if path.is_file()
+                && path.extension().is_some_and(|extension| extension == "lp")
+            {
+                encodings.push(path);
+            }
+        }
+    }
+
+    encodings
+}
+
+#[test]
+fn program_should_contain_included_files_from_disk() {
+    let folder = TestFolder::new("workspace_program");
+    let directory = &folder.path;
+    std::fs::write(
+        directory.join("encoding.lp"),
+        "#include \"instance.lp\".\nb(X) :- a(X).",
+    )
+    .unwrap();
+    std::fs::write(directory.join("instance.lp"), "a(1). a(2).").unwrap();
+
+    let workspace = Workspace::new();
+    let encoding = Url::from_file_path(directory.join("encoding.lp")).unwrap();
+    let program = workspace.get_program(&encoding, &DashMap::new());
+
+    assert_eq!(program.documents.len(), 2);
+    assert_eq!(
+        program
+            .predicate_semantics
+            .predicates
+            .get(&("a".to_string(), 1))
+            .unwrap()
+            .len(),
+        3
+    );
+}
+
+#[test]
+fn program_should_prefer_open_documents_and_survive_include_cycles() {
+    let folder = TestFolder::new("workspace_cycle");
+    let directory = &folder.path;
+    std::fs::write(directory.join("a.lp"), "#include \"b.lp\".").unwrap();
+    std::fs::write(directory.join("b.lp"), "#include \"a.lp\".\nb.").unwrap();
+
+    let a = Url::from_file_path(directory.join("a.lp")).unwrap();
+    let open_documents = DashMap::new();
+    open_documents.insert(
+        a.to_string(),
+        DocumentData::from_source(a.clone(), "#include \"b.lp\".\nopen.", 1),
+    );
+
+    let program = Workspace::new().get_program(&a, &open_documents);
+
+    assert_eq!(program.documents.len(), 2);
+    assert!(program
+        .predicate_semantics
+        .predicates
+        .contains_key(&("open".to_string(), 0)));
+    assert!(program
+        .predicate_semantics
+        .predicates
+        .contains_key(&("b".to_string(), 0)));
+}
+
+#[test]
+fn indexing_should_find_encodings_in_nested_folders() {
+    let folder = TestFolder::new("workspace_index");
+    let directory = &folder.path;
+    std::fs::create_dir_all(directory.join("instances")).unwrap();
+    std::fs::create_dir_all(directory.join(".git")).unwrap();
+    std::fs::write(directory.join("encoding.lp"), "b(X) :- a(X).").unwrap();
+    std::fs::write(directory.join("instances").join("instance.lp"), "a(1).").unwrap();
+    std::fs::write(directory.join("instances").join("notes.txt"), "a(2).").unwrap();
+    std::fs::write(directory.join(".git").join("hidden.lp"), "a(3).").unwrap();
+
+    let workspace = Workspace::new();
+    workspace.index_folders(vec![directory.clone()]);
+
+    assert_eq!(workspace.files.len(), 2);
+    assert!(workspace.files.contains_key(
+        &Url::from_file_path(directory.join("instances").join("instance.lp"))
+            .unwrap()
+            .to_string()
+    ));
+
+    workspace.remove_folders(vec![Url::from_file_path(directory).unwrap()]);
+
+    assert_eq!(workspace.files.len(), 0);
+}
+
+#[cfg(unix)]
+#[test]
+fn indexing_should_not_follow_symbolic_links_to_folders() {
+    let folder = TestFolder::new("workspace_symlink");
+    let directory = &folder.path;
+    std::fs::create_dir_all(directory.join("instances")).unwrap();
+    std::fs::write(directory.join("instances").join("instance.lp"), "a(1).").unwrap();
+    std::os::unix::fs::symlink(directory, directory.join("instances").join("loop")).unwrap();
+
+    assert_eq!(
+        find_encodings(directory),
+        vec![directory.join("instances").join("instance.lp")]
+    );
+}
+
+#[test]
+fn includes_should_be_found_in_the_include_paths() {
+    let folder = TestFolder::new("workspace_include_paths");
+    let directory = &folder.path;
+    std::fs::create_dir_all(directory.join("project")).unwrap();
+    std::fs::create_dir_all(directory.join("lib")).unwrap();
+    std::fs::write(directory.join("project").join("encoding.lp"), "#include \"graph.lp\".").unwrap();
+    std::fs::write(directory.join("lib").join("graph.lp"), "edge(1,2).").unwrap();
+
+    let workspace = Workspace::new();
+    workspace.set_folders(vec![Url::from_file_path(directory.join("project")).unwrap()]);
+    let encoding = Url::from_file_path(directory.join("project").join("encoding.lp")).unwrap();
+
+    assert_eq!(workspace.get_program(&encoding, &DashMap::new()).documents.len(), 1);
+
+    workspace.set_include_paths(vec![PathBuf::from("../lib")]);
+
+    assert_eq!(workspace.get_program(&encoding, &DashMap::new()).documents.len(), 2);
+}
+
+#[test]
+fn programs_should_be_cached_until_a_document_changes() {
+    let uri = Url::parse("file:///cached.lp").unwrap();
+    let open_documents = DashMap::new();
+    open_documents.insert(uri.to_string(), DocumentData::from_source(uri.clone(), "a.", 1));
+
+    let workspace = Workspace::new();
+    let program = workspace.get_program(&uri, &open_documents);
+    assert!(Arc::ptr_eq(&program, &workspace.get_program(&uri, &open_documents)));
+    let indexed = workspace.get_indexed_program(&open_documents);
+    assert!(Arc::ptr_eq(&indexed, &workspace.get_indexed_program(&open_documents)));
+
+    open_documents.insert(uri.to_string(), DocumentData::from_source(uri.clone(), "b.", 2));
+    workspace.invalidate_programs();
+
+    let program = workspace.get_program(&uri, &open_documents);
+    assert!(program
+        .predicate_semantics
+        .predicates
+        .contains_key(&("b".to_string(), 0)));
+    assert!(workspace
+        .get_indexed_program(&open_documents)
+        .predicate_semantics
+        .predicates
+        .contains_key(&("b".to_string(), 0)));
+}
+
+#[test]
+fn closed_documents_should_stay_in_the_workspace() {
+    let folder = TestFolder::new("workspace_close");
+    let path = folder.path.join("encoding.lp");
+    std::fs::write(&path, "a(1).").unwrap();
+    let uri = Url::from_file_path(&path).unwrap();
+
+    let workspace = Workspace::new();
+    workspace.index_folders(vec![folder.path.clone()]);
+
+    // The document is opened, changed and saved, and closed afterwards
+    let open_documents = DashMap::new();
+    open_documents.insert(uri.to_string(), DocumentData::from_source(uri.clone(), "b(1).", 1));
+    std::fs::write(&path, "b(1).").unwrap();
+    workspace.reload_file(&uri);
+    open_documents.remove(&uri.to_string());
+    workspace.reload_file(&uri);
+
+    let program = workspace.get_indexed_program(&open_documents);
+    assert!(program
+        .predicate_semantics
+        .predicates
+        .contains_key(&("b".to_string(), 1)));
+    assert!(!program
+        .predicate_semantics
+        .predicates
+        .contains_key(&("a".to_string(), 1)));
+}
+
+#[test]
+fn included_files_should_be_analyzed_within_their_root_files() {
+    let folder = TestFolder::new("workspace_root_program");
+    let directory = &folder.path;
+    std::fs::write(
+        directory.join("main.lp"),
+        "#include \"defs.lp\". #include \"rules.lp\".",
+    )
+    .unwrap();
+    std::fs::write(directory.join("defs.lp"), "edge(1,2).").unwrap();
+    std::fs::write(directory.join("rules.lp"), "reach(X) :- edge(X,_).").unwrap();
+    std::fs::write(directory.join("other.lp"), "a.").unwrap();
+
+    let workspace = Workspace::new();
+    workspace.index_folders(vec![directory.clone()]);
+    let program_of = |name: &str| {
+        let uri = Url::from_file_path(directory.join(name)).unwrap();
+        workspace.get_root_program(&uri, &DashMap::new())
+    };
+
+    let program = program_of("rules.lp");
+    assert_eq!(program.documents.len(), 3);
+    assert!(program
+        .predicate_semantics
+        .predicates
+        .contains_key(&("edge".to_string(), 2)));
+    assert_eq!(program_of("main.lp").documents.len(), 3);
+    assert_eq!(program_of("other.lp").documents.len(), 1);
+}
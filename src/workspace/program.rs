@@ -0,0 +1,42 @@
+use tower_lsp::lsp_types::Url;
+
+use crate::{document::DocumentData, semantics::predicate_semantics::PredicateSemantics};
+
+/**
+ * A program is an encoding together with every file it includes, directly or through other includes
+ */
+#[derive(Clone, Debug)]
+pub struct Program {
+    /**
+     * The documents that are part of this program, the first one is the document the program was built for
+     */
+    pub documents: Vec<DocumentData>,
+
+    /**
+     * The predicates of all documents of this program combined
+     */
+    pub predicate_semantics: PredicateSemantics,
+}
+
+impl Program {
+    pub fn new(documents: Vec<DocumentData>) -> Program {
+        let predicate_semantics = PredicateSemantics::merge(
+            documents
+                .iter()
+                .map(|document| &document.semantics.predicate_semantics)
+                .collect(),
+        );
+
+        Program {
+            documents,
+            predicate_semantics,
+        }
+    }
+
+    /**
+     * Get the document of this program with a specific uri
+     */
+    pub fn get_document(&self, uri: &Url) -> Option<&DocumentData> {
+        self.documents.iter().find(|document| &document.uri == uri)
+    }
+}